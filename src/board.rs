@@ -0,0 +1,263 @@
+use std::ops::Range;
+
+// The game map, stored in two layers.
+//
+// The first one only says which cells are taken: every line is a `u16` where the bit `x` is
+// set when the block in column `x` is taken. Checking whether a piece fits is then just
+// a few shifts and ANDs per line, and a line is full when all its bits are set.
+//
+// The second one is the color of every block (0 for an empty cell), which only
+// the renderer cares about. Both are always kept in sync.
+//
+// A line being a `u16`, a game map can't be more than 16 blocks wide.
+pub const MAX_WIDTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    rows: Vec<u16>,
+    colors: Vec<u8>,
+}
+
+impl Board {
+    // Creates an empty game map of `width` blocks (at most `MAX_WIDTH`) and `height` lines.
+    pub fn new(width: usize, height: usize) -> Board {
+        let width = width.min(MAX_WIDTH);
+        Board {
+            width,
+            rows: vec![0; height],
+            colors: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    // The bits of a line with every block taken.
+    pub fn full_row(&self) -> u16 {
+        ((1u32 << self.width) - 1) as u16
+    }
+
+    // The occupancy of the given line: the bit `x` is set when the cell in column `x` is taken.
+    pub fn row(&self, y: usize) -> u16 {
+        self.rows[y]
+    }
+
+    // The colors of the blocks of the given line, from left to right.
+    pub fn line(&self, y: usize) -> &[u8] {
+        &self.colors[y * self.width..(y + 1) * self.width]
+    }
+
+    // Every line of the game map from the top, for anyone who wants to look at the cells.
+    pub fn lines(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.colors.chunks(self.width)
+    }
+
+    // The color of the block at (x, y), or `None` if this isn't in the game map at all.
+    pub fn get(&self, x: isize, y: isize) -> Option<u8> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height() {
+            None
+        } else {
+            Some(self.colors[y as usize * self.width + x as usize])
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+
+    // Puts a block of the given color at (x, y). A color of 0 removes the block.
+    pub(crate) fn set(&mut self, x: usize, y: usize, color: u8) {
+        self.colors[y * self.width + x] = color;
+        if color == 0 {
+            self.rows[y] &= !(1 << x);
+        } else {
+            self.rows[y] |= 1 << x;
+        }
+    }
+
+    // Pushes everything up by one line and adds a line of blocks of the given color
+    // at the bottom, with a hole in the given columns.
+    // Returns false if there were blocks on the top line: they've been pushed out of the map.
+    pub(crate) fn push_line(&mut self, holes: Range<usize>, color: u8) -> bool {
+        let width = self.width;
+        let fits = self.rows[0] == 0;
+        let holes_mask = holes.clone().fold(0, |mask, x| mask | 1 << x);
+        self.rows.remove(0);
+        self.rows.push(self.full_row() & !holes_mask);
+        self.colors.drain(0..width);
+        self.colors.extend((0..width).map(|x| if holes.contains(&x) { 0 } else { color }));
+        fits
+    }
+
+    // Checks whether a piece fits with the top-left corner of its mask at (x, y).
+    // Every line of the mask works like the lines of the game map: bit `n` is column `n`.
+    // Going out of the game map doesn't fit, whatever the side.
+    pub(crate) fn fits(&self, mask: &[u16], x: isize, y: isize) -> bool {
+        let full = self.full_row() as u32;
+        for (decal_y, &line) in mask.iter().enumerate() {
+            if line == 0 {
+                continue;
+            }
+            let y = y + decal_y as isize;
+            if y < 0 || y as usize >= self.height() {
+                return false;
+            }
+            let line = line as u32;
+            let shifted = if x >= 0 {
+                // Anything shifted past the last column is caught below.
+                match line.checked_shl(x as u32) {
+                    Some(shifted) => shifted,
+                    None => return false,
+                }
+            } else {
+                // The blocks pushed out on the left are out of the game map.
+                if (-x) as u32 >= u16::BITS || line & ((1 << -x) - 1) != 0 {
+                    return false;
+                }
+                line >> -x
+            };
+            if shifted & !full != 0 || shifted & self.rows[y as usize] as u32 != 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    // The lines which are full, from the top.
+    // The lines go by groups of `group` lines counted from the bottom (the pairs of lines
+    // of the big mode), and only a group where every line is full counts.
+    pub(crate) fn full_lines(&self, group: usize) -> Vec<usize> {
+        let full = self.full_row();
+        let height = self.height();
+        let group = group.max(1);
+        (0..height)
+            .filter(|&y| {
+                let bottom = height - 1 - (height - 1 - y) / group * group;
+                let top = (bottom + 1).saturating_sub(group);
+                self.rows[top..=bottom].iter().all(|&row| row == full)
+            })
+            .collect()
+    }
+
+    // Removes every full line (by groups of `group` lines, see `full_lines`) and makes
+    // everything above fall down, then returns how many lines were removed.
+    //
+    // Instead of moving the lines one by one, we just keep the lines which aren't full
+    // and put as many empty lines as we removed at the top of the map.
+    pub(crate) fn clear_lines(&mut self, group: usize) -> u32 {
+        let full_lines = self.full_lines(group);
+        let width = self.width;
+        let height = self.height();
+        let mut rows = Vec::with_capacity(height);
+        let mut colors = Vec::with_capacity(self.colors.len());
+        for (y, &row) in self.rows.iter().enumerate() {
+            if !full_lines.contains(&y) {
+                rows.push(row);
+                colors.extend_from_slice(&self.colors[y * width..(y + 1) * width]);
+            }
+        }
+        let removed = height - rows.len();
+        let mut new_rows = vec![0; removed];
+        new_rows.extend(rows);
+        let mut new_colors = vec![0; removed * width];
+        new_colors.extend(colors);
+        self.rows = new_rows;
+        self.colors = new_colors;
+        removed as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A vertical bar of 2 blocks, and the same bar with a hole in its top-left corner.
+    const BAR: [u16; 2] = [0b1, 0b1];
+    const HOOK: [u16; 2] = [0b10, 0b11];
+
+    fn fill_row(board: &mut Board, y: usize) {
+        for x in 0..board.width() {
+            board.set(x, y, 1);
+        }
+    }
+
+    #[test]
+    fn walls_and_floor() {
+        let board = Board::new(10, 4);
+        assert!(board.fits(&BAR, 0, 0));
+        assert!(board.fits(&BAR, 9, 2));
+        // Left wall, right wall and floor.
+        assert!(!board.fits(&BAR, -1, 0));
+        assert!(!board.fits(&BAR, 10, 0));
+        assert!(!board.fits(&BAR, 0, 3));
+        assert!(!board.fits(&BAR, 0, -1));
+        // Shifts bigger than a line don't wrap around.
+        assert!(!board.fits(&BAR, -20, 0));
+        assert!(!board.fits(&BAR, 40, 0));
+    }
+
+    #[test]
+    fn negative_x_with_empty_columns() {
+        let board = Board::new(10, 4);
+        // The first column of the mask is empty on its first line only.
+        let hook_top = [0b10, 0b00];
+        assert!(board.fits(&hook_top, -1, 0));
+        assert!(!board.fits(&HOOK, -1, 0));
+        assert!(!board.fits(&hook_top, -2, 0));
+    }
+
+    #[test]
+    fn blocks_of_the_stack() {
+        let mut board = Board::new(10, 4);
+        board.set(3, 2, 1);
+        assert!(!board.fits(&BAR, 3, 2));
+        assert!(!board.fits(&BAR, 3, 1));
+        assert!(board.fits(&BAR, 3, 0));
+        assert!(board.fits(&BAR, 4, 2));
+        // The hole of the hook goes around the block.
+        assert!(board.fits(&HOOK, 3, 2));
+        assert!(!board.fits(&HOOK, 2, 2));
+    }
+
+    #[test]
+    fn sixteen_wide() {
+        let mut board = Board::new(16, 4);
+        assert_eq!(board.full_row(), u16::MAX);
+        assert!(board.fits(&BAR, 15, 0));
+        assert!(!board.fits(&BAR, 16, 0));
+        assert!(!board.fits(&HOOK, 15, 0));
+        board.set(15, 3, 1);
+        assert!(!board.fits(&BAR, 15, 2));
+        fill_row(&mut board, 2);
+        assert_eq!(board.clear_lines(1), 1);
+        assert_eq!(board.row(3), 1 << 15);
+        assert_eq!(board.get(15, 3), Some(1));
+        assert_eq!(board.get(16, 3), None);
+    }
+
+    #[test]
+    fn full_lines_by_pairs() {
+        let mut board = Board::new(4, 6);
+        // A full pair at the bottom, and a full line above it which is only half of its pair.
+        fill_row(&mut board, 5);
+        fill_row(&mut board, 4);
+        fill_row(&mut board, 3);
+        board.set(0, 2, 1);
+        assert_eq!(board.full_lines(1), vec![3, 4, 5]);
+        assert_eq!(board.full_lines(2), vec![4, 5]);
+        // The pairs are counted from the bottom: rows 2 and 3 are one, not rows 3 and 4.
+        fill_row(&mut board, 2);
+        assert_eq!(board.full_lines(2), vec![2, 3, 4, 5]);
+        board.set(0, 2, 0);
+        assert_eq!(board.clear_lines(2), 2);
+        assert_eq!(board.row(5), board.full_row());
+        assert_eq!(board.row(4), board.full_row() & !1);
+        assert_eq!(board.row(3), 0);
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::input::Handling;
+use crate::level::{GravityCurve, LevelGoal};
+use crate::lock::LockMode;
+use crate::mode::GameMode;
+use crate::randomizer::RandomizerKind;
+use crate::rotation::{RotationSystem, Srs};
+use crate::visibility::StackVisibility;
+
+// How many cells of the game map a block takes on each side in big mode.
+pub const BIG_SCALE: usize = 2;
+
+// Every rule which can change from one game to another.
+// Start from `GameConfig::default()` and only change what you need:
+//
+//     let config = GameConfig { rotation_system: Arc::new(Ars), ..GameConfig::default() };
+#[derive(Clone)]
+pub struct GameConfig {
+    // What the player is trying to do.
+    pub mode: GameMode,
+    // How the tetriminos look and rotate.
+    pub rotation_system: Arc<dyn RotationSystem>,
+    // Which tetrimino comes next.
+    pub randomizer: RandomizerKind,
+    // The size of the game map the player sees, in blocks. It can't be more than 16 blocks
+    // wide (`board::MAX_WIDTH`, a line being a `u16`): a wider game map gets cut down to 16.
+    pub width: usize,
+    pub height: usize,
+    // How many lines the game map has above the visible part, from 2 to 20.
+    // The pieces spawn there, and the stack can go up there too.
+    pub hidden_rows: usize,
+    // How many upcoming pieces the player can see, from 0 to 6.
+    pub preview_count: usize,
+    // Whether the player is shown where the current piece is going to land.
+    pub ghost: bool,
+    // Whether the player can see the blocks of the stack, or only for a while.
+    pub stack_visibility: StackVisibility,
+    // TGM's big mode: every block of the tetriminos takes 2x2 cells of the game map.
+    // The pieces move and fall by two cells, and the lines only go away by pairs.
+    pub big: bool,
+    // How a piece on the ground locks, and how many frames it waits before doing so.
+    pub lock_mode: LockMode,
+    pub lock_delay: u32,
+    // How the piece moves when left or right is held.
+    pub handling: Handling,
+    // The level the game starts at, what it takes to reach the next one
+    // and how fast the pieces fall at each of them.
+    pub start_level: u32,
+    pub level_goal: LevelGoal,
+    pub gravity: GravityCurve,
+    // Where all the randomness of the game comes from. Two games with the same seed
+    // (and the same rules) get exactly the same pieces.
+    pub seed: u64,
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig {
+            mode: GameMode::default(),
+            rotation_system: Arc::new(Srs),
+            randomizer: RandomizerKind::default(),
+            width: 10,
+            height: 20,
+            hidden_rows: 2,
+            preview_count: 5,
+            ghost: true,
+            stack_visibility: StackVisibility::default(),
+            big: false,
+            lock_mode: LockMode::default(),
+            lock_delay: 30,
+            handling: Handling::default(),
+            start_level: 1,
+            level_goal: LevelGoal::default(),
+            gravity: GravityCurve::default(),
+            // Nobody asked for a specific game, so we pick one at random.
+            seed: rand::random(),
+        }
+    }
+}
+
+impl GameConfig {
+    // How many cells of the game map a block takes on each side.
+    pub fn scale(&self) -> usize {
+        if self.big {
+            BIG_SCALE
+        } else {
+            1
+        }
+    }
+}
@@ -0,0 +1,1015 @@
+use crate::board::{Board, MAX_WIDTH};
+use crate::config::GameConfig;
+use crate::garbage::{GarbageGenerator, GARBAGE_COLOR};
+use crate::input::{AutoShift, Inputs, Shift, Timing, FRAMES_PER_SECOND};
+use crate::level::{Levels, MAX_START_LEVEL, ONE_G, TWENTY_G};
+use crate::lock::LockDelay;
+use crate::mode::GameMode;
+use crate::randomizer::{PieceQueue, QUEUE_LENGTH};
+use crate::rng::{rng_from_seed, GameRng};
+use crate::scoring::{detect_t_spin, Clear, ClearStats, Scoring, TSpin};
+use crate::tetrimino::{PieceKind, Rotation, Tetrimino};
+use crate::timer::Stopwatch;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// While the player holds the soft drop, the gravity is 20 times faster.
+const SOFT_DROP_FACTOR: u32 = 20;
+// The points earned for every row the player makes the piece go down by themselves.
+const SOFT_DROP_POINTS: u32 = 1;
+const HARD_DROP_POINTS: u32 = 2;
+
+// The smallest game map the pieces fit in (the biggest is up to the board),
+// and how many hidden lines there can be above it.
+const MIN_WIDTH: usize = 4;
+const MIN_HEIGHT: usize = 4;
+const MIN_HIDDEN_ROWS: usize = 2;
+const MAX_HIDDEN_ROWS: usize = 20;
+
+// How many lines at the top of the visible part of the game map the garbage of a dig race
+// always leaves free, so a piece has somewhere to go.
+const GARBAGE_ROOM: usize = 4;
+
+// A split time is taken every time this many more lines are cleared.
+const SPLIT_LINES: u32 = 10;
+
+// How many placements the zen mode remembers, so a long session doesn't eat all the memory.
+const MAX_UNDO: usize = 1000;
+
+// Tetris will hold all the game's information:
+// - Game map
+// - Current level
+// - Score
+// - Number of lines
+// - The current tetrimino
+// - Some potential other information ( such as a ghost, or the preview of the next tetrimino )
+pub struct Tetris {
+    pub game_map: Board,
+    pub current_level: u32,
+    pub score: u32,
+    pub nb_lines: u32,
+    pub current_piece: Option<Tetrimino>,
+}
+
+impl Tetris {
+    // Creates an empty game map of `width` blocks and `height` lines.
+    // The height counts every line, the hidden ones above the visible part included.
+    pub fn new(width: usize, height: usize) -> Tetris {
+        Tetris {
+            game_map: Board::new(width, height),
+            current_level: 1,
+            score: 0,
+            nb_lines: 0,
+            current_piece: None,
+        }
+    }
+
+    // Writes the blocks of the current tetrimino into the game map.
+    // Once done, the piece is gone: it's part of the stack now.
+    fn make_permanent(&mut self) {
+        if let Some(piece) = self.current_piece.take() {
+            for (decal_y, line) in piece.shape().iter().enumerate() {
+                for (decal_x, &cell) in line.iter().enumerate() {
+                    if cell != 0 {
+                        let x = (piece.x() + decal_x as isize) as usize;
+                        let y = (piece.y() + decal_y as isize) as usize;
+                        self.game_map.set(x, y, cell);
+                    }
+                }
+            }
+        }
+    }
+
+    // Removes every full line and returns how many lines were removed.
+    // In big mode, every pair of lines only counts as one: they're one line of big blocks.
+    fn check_lines(&mut self, scale: usize) -> u32 {
+        let removed = self.game_map.clear_lines(scale) / scale as u32;
+        self.nb_lines += removed;
+        removed
+    }
+}
+
+// The usual game map: 10 blocks wide, 20 lines visible and 2 hidden above them.
+impl Default for Tetris {
+    fn default() -> Tetris {
+        Tetris::new(10, 22)
+    }
+}
+
+// Everything worth reporting that happened during a frame.
+// The frontend can use them to play sounds, show animations or just ignore them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    PieceSpawned,
+    PieceHeld,
+    PieceLocked,
+    LinesCleared(u32),
+    // The game just went to the given level.
+    LevelUp(u32),
+    // The piece which just locked earned points: a line clear, a T-spin...
+    Scored(Clear),
+    // The player just cleared another `SPLIT_LINES` lines.
+    Split(Duration),
+    // The goal of the mode is reached: the game is won and over.
+    Completed,
+    GameOver,
+}
+
+// Everything the game needs to go back to the moment a piece came in, for the undo.
+// The piece itself comes in again from the top, so only its kind is kept.
+#[derive(Clone)]
+struct Snapshot {
+    game_map: Board,
+    piece: PieceKind,
+    level: u32,
+    score: u32,
+    nb_lines: u32,
+    rng: GameRng,
+    queue: PieceQueue,
+    held_piece: Option<PieceKind>,
+    hold_used: bool,
+    scoring: Scoring,
+    levels: Levels,
+}
+
+// Game is the public face of the engine: you create it, then call `step` once per frame
+// with the buttons held during that frame and it tells you what happened.
+pub struct Game {
+    config: GameConfig,
+    tetris: Tetris,
+    // Every random thing happening in the game comes from here.
+    rng: GameRng,
+    // The tetriminos which will come after the current one, so the player can plan ahead.
+    queue: PieceQueue,
+    // The tetrimino put aside by the player, if any.
+    held_piece: Option<PieceKind>,
+    // The player can only hold once per piece, otherwise they could keep
+    // swapping pieces forever to stop the gravity.
+    hold_used: bool,
+    previous_inputs: Inputs,
+    auto_shift: AutoShift,
+    // How far the current tetrimino went down since it last moved to a new row,
+    // in 1/65536 of a row (see `level::ONE_G`).
+    gravity_counter: u32,
+    lock_delay: LockDelay,
+    scoring: Scoring,
+    levels: Levels,
+    // The kick used by the last rotation, as long as the piece didn't move since then.
+    // A T-spin only counts if the last thing the piece did was to rotate.
+    last_rotation: Option<usize>,
+    // The time of the game: it starts with the first button the player presses
+    // and stops when the game ends.
+    stopwatch: Stopwatch,
+    // The same thing in frames, for the time limit: the clock of the computer doesn't
+    // get a say in how a game goes, so the same inputs always give the same game.
+    played_frames: u32,
+    splits: Vec<Duration>,
+    garbage: GarbageGenerator,
+    // The garbage lines still in the game map: they're always the lowest ones.
+    garbage_left: u32,
+    garbage_cleared: u32,
+    // How many frames since the last garbage line came up, when it rises.
+    garbage_timer: u32,
+    // The lines the last piece which locked removed, numbered as they were before.
+    cleared_rows: Vec<usize>,
+    // The zen mode only: the state when the current piece came in, the states before it
+    // (the latest one at the back) and the ones the player went back from with the undo.
+    turn_start: Option<Snapshot>,
+    undo_stack: VecDeque<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    gravity_enabled: bool,
+    game_over: bool,
+    completed: bool,
+}
+
+impl Game {
+    pub fn new() -> Game {
+        Game::with_config(GameConfig::default())
+    }
+
+    pub fn with_config(mut config: GameConfig) -> Game {
+        // The pieces have to fit in the game map and have some room to spawn above it.
+        // In big mode, the game map is made of 2x2 cells: every size has to be even.
+        let scale = config.scale();
+        config.width = config.width.clamp(MIN_WIDTH * scale, MAX_WIDTH);
+        config.width -= config.width % scale;
+        config.height = config.height.max(MIN_HEIGHT * scale);
+        config.height += config.height % scale;
+        config.hidden_rows = config.hidden_rows.clamp(MIN_HIDDEN_ROWS * scale, MAX_HIDDEN_ROWS);
+        config.hidden_rows += config.hidden_rows % scale;
+        // The garbage leaves some room for the pieces at the top of the visible part
+        // of the game map, and a dig race without any garbage would be won with the first line.
+        if let GameMode::Dig { ref mut rows, .. } = config.mode {
+            let max_rows = (config.height / scale).saturating_sub(GARBAGE_ROOM) as u32;
+            *rows = (*rows).min(max_rows).max(1);
+        }
+        let mut rng = rng_from_seed(config.seed);
+        let lock_delay = LockDelay::new(config.lock_mode, config.lock_delay);
+        let auto_shift = AutoShift::new(config.handling);
+        let levels = Levels::new(config.level_goal);
+        let garbage = match config.mode {
+            GameMode::Dig { garbage, .. } => garbage,
+            _ => Default::default(),
+        };
+        let garbage = GarbageGenerator::new(garbage, config.seed);
+        let queue = PieceQueue::new(config.randomizer.build(), &mut rng);
+        let mut tetris = Tetris::new(config.width, config.height + config.hidden_rows);
+        tetris.current_level = config.start_level.clamp(1, MAX_START_LEVEL);
+        let mut game = Game {
+            config,
+            tetris,
+            rng,
+            queue,
+            held_piece: None,
+            hold_used: false,
+            previous_inputs: Inputs::default(),
+            auto_shift,
+            gravity_counter: 0,
+            lock_delay,
+            scoring: Scoring::new(),
+            levels,
+            last_rotation: None,
+            stopwatch: Stopwatch::default(),
+            played_frames: 0,
+            splits: Vec::new(),
+            garbage,
+            garbage_left: 0,
+            garbage_cleared: 0,
+            garbage_timer: 0,
+            cleared_rows: Vec::new(),
+            turn_start: None,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            gravity_enabled: true,
+            game_over: false,
+            completed: false,
+        };
+        // The garbage leaves room for the pieces (see above),
+        // so the first piece always fits: nothing worth reporting.
+        if let GameMode::Dig { rows, .. } = game.config.mode {
+            for _ in 0..rows {
+                game.add_garbage(&mut Vec::new());
+            }
+        }
+        game.spawn(&mut Vec::new());
+        game.start_turn();
+        game
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    // The seed the game was started with: starting another game with it
+    // gives exactly the same pieces.
+    pub fn seed(&self) -> u64 {
+        self.config.seed
+    }
+
+    pub fn tetris(&self) -> &Tetris {
+        &self.tetris
+    }
+
+    // The tetriminos coming after the current one, the next one first.
+    pub fn upcoming_pieces(&self) -> impl Iterator<Item = PieceKind> + '_ {
+        self.queue.peek()
+    }
+
+    // The upcoming tetriminos the player is allowed to see, depending on `preview_count`.
+    pub fn preview(&self) -> impl Iterator<Item = PieceKind> + '_ {
+        self.queue.peek().take(self.config.preview_count.min(QUEUE_LENGTH))
+    }
+
+    // The `y` the current piece would land at if it fell right now: this is where the ghost
+    // is drawn. There is no ghost when it's disabled or when there is no current piece.
+    pub fn ghost_y(&self) -> Option<isize> {
+        if !self.config.ghost {
+            return None;
+        }
+        self.tetris
+            .current_piece
+            .as_ref()
+            .map(|piece| piece.landing_y(&self.tetris.game_map))
+    }
+
+    // How close the current piece is to locking, from 0 to 1,
+    // so the frontend can show it (by fading the piece, for example).
+    pub fn lock_progress(&self) -> f32 {
+        self.lock_delay.progress()
+    }
+
+    pub fn held_piece(&self) -> Option<PieceKind> {
+        self.held_piece
+    }
+
+    // Whether the player already held during the current piece, so it can't be done again
+    // until the piece locks.
+    pub fn hold_used(&self) -> bool {
+        self.hold_used
+    }
+
+    // How many lines the player still needs to clear to reach the next level.
+    // With the variable goal, a difficult clear counts for more than its lines.
+    pub fn lines_to_next_level(&self) -> u32 {
+        self.levels.remaining(self.tetris.current_level)
+    }
+
+    // How fast the pieces fall right now, in 1/65536 of a row per frame:
+    // the gravity of the current level, or nothing when it's turned off.
+    pub fn gravity(&self) -> u32 {
+        if self.gravity_enabled {
+            self.config.gravity.gravity(self.tetris.current_level)
+        } else {
+            0
+        }
+    }
+
+    pub fn gravity_enabled(&self) -> bool {
+        self.gravity_enabled
+    }
+
+    // Turns the gravity on or off in the zen mode, right in the middle of the game:
+    // without it, the pieces only go down with the drops. Returns whether it's on now.
+    pub fn toggle_gravity(&mut self) -> bool {
+        if self.config.mode == GameMode::Zen {
+            self.gravity_enabled = !self.gravity_enabled;
+            self.gravity_counter = 0;
+        }
+        self.gravity_enabled
+    }
+
+    // Makes the given tetrimino the next one in the zen mode, instead of the one
+    // the randomizer picked. Returns false in any other mode.
+    pub fn choose_next_piece(&mut self, kind: PieceKind) -> bool {
+        if self.config.mode != GameMode::Zen {
+            return false;
+        }
+        self.queue.replace_next(kind);
+        if let Some(ref mut turn_start) = self.turn_start {
+            turn_start.queue.replace_next(kind);
+        }
+        // What comes after isn't what the player went back from anymore.
+        self.redo_stack.clear();
+        true
+    }
+
+    // How many placements the player can go back from, and come back to, in the zen mode.
+    pub fn undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    pub fn redo_count(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    // Goes back to the moment the previous piece came in, as if it was never placed,
+    // so a misdrop can be tried again. Only the zen mode remembers the placements.
+    // Returns false when there is nothing to go back to.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop_back() {
+            Some(snapshot) => {
+                self.redo_stack.extend(self.turn_start.take());
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Comes back to where the last undo went back from.
+    // Returns false when there is nothing to come back to.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(snapshot) => {
+                self.undo_stack.extend(self.turn_start.take());
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // How long the game has been going on, or how long it lasted once it's over.
+    pub fn time(&self) -> Duration {
+        self.stopwatch.elapsed()
+    }
+
+    // The time at which every `SPLIT_LINES` lines were cleared.
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
+    // How many garbage lines are still in the game map, and how many the player cleared.
+    pub fn garbage_left(&self) -> u32 {
+        self.garbage_left
+    }
+
+    pub fn garbage_cleared(&self) -> u32 {
+        self.garbage_cleared
+    }
+
+    // The lines removed by the last piece which locked, from the top, numbered as they were
+    // before being removed. Frontends keeping something for every cell (such as an animation)
+    // can remove the same lines when they get `GameEvent::LinesCleared`.
+    pub fn cleared_rows(&self) -> &[usize] {
+        &self.cleared_rows
+    }
+
+    // How many frames until the next garbage line comes up, when the garbage rises.
+    pub fn next_garbage_in(&self) -> Option<u32> {
+        match self.config.mode {
+            GameMode::Dig { rise: Some(rise), .. } => Some(rise.to_frames().max(1) - self.garbage_timer),
+            _ => None,
+        }
+    }
+
+    // How many clears of every kind the player did so far.
+    pub fn clear_stats(&self) -> &ClearStats {
+        self.scoring.stats()
+    }
+
+    // Whether the stack reached the top.
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    // How much time is left before the end of a timed mode (like ultra), if there is a limit.
+    // It's counted in frames, like the limit itself.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.time_limit_frames().map(|limit| {
+            let frames = limit.saturating_sub(self.played_frames) as u64;
+            Duration::from_millis(frames * 1000 / FRAMES_PER_SECOND as u64)
+        })
+    }
+
+    // How many frames the time limit of the mode lasts, if there is one.
+    fn time_limit_frames(&self) -> Option<u32> {
+        self.config.mode.time_limit().map(|limit| {
+            Timing::Millis(limit.as_millis().min(u32::MAX as u128) as u32).to_frames()
+        })
+    }
+
+    // Whether the player reached the goal of the mode (like the 40 lines of a sprint,
+    // or the end of the 2 minutes of an ultra).
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    // Advances the game by one frame.
+    //
+    // Most keys only do something on the frame they get pressed, otherwise holding `rotate`
+    // for half a second would spin the piece like crazy. Left and right go through the auto
+    // shift instead, and the soft drop is about holding the key, not pressing it.
+    pub fn step(&mut self, inputs: Inputs) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.game_over || self.completed {
+            return events;
+        }
+        let pressed = inputs.pressed_since(self.previous_inputs);
+        self.previous_inputs = inputs;
+        if pressed != Inputs::default() {
+            self.stopwatch.start();
+        }
+        if self.stopwatch.is_running() {
+            self.played_frames = self.played_frames.saturating_add(1);
+        }
+        if let Some(limit) = self.time_limit_frames() {
+            if self.played_frames > limit {
+                self.complete(&mut events);
+                return events;
+            }
+        }
+        // The garbage only starts rising once the player started playing.
+        if let GameMode::Dig { rise: Some(rise), .. } = self.config.mode {
+            if self.stopwatch.is_running() {
+                self.garbage_timer += 1;
+                if self.garbage_timer >= rise.to_frames().max(1) {
+                    self.garbage_timer = 0;
+                    self.add_garbage(&mut events);
+                    if self.game_over {
+                        return events;
+                    }
+                }
+            }
+        }
+        let shift = self.auto_shift.update(inputs, pressed);
+
+        if pressed.hold {
+            self.hold(&mut events);
+            if self.game_over {
+                return events;
+            }
+        }
+
+        let rotation_system = &*self.config.rotation_system;
+        let Tetris { ref game_map, ref mut current_piece, .. } = self.tetris;
+        if let Some(piece) = current_piece {
+            let mut moved = false;
+            if let Shift::Move { direction, steps } = shift {
+                for _ in 0..steps {
+                    let (x, y) = (piece.x(), piece.y());
+                    if !piece.change_position(game_map, x + direction * piece.scale() as isize, y) {
+                        break;
+                    }
+                    moved = true;
+                }
+            }
+            let kick = if pressed.rotate_cw {
+                piece.rotate(game_map, Rotation::Clockwise, rotation_system)
+            } else if pressed.rotate_ccw {
+                piece.rotate(game_map, Rotation::CounterClockwise, rotation_system)
+            } else {
+                None
+            };
+            let rotated = kick.is_some();
+            if rotated {
+                self.auto_shift.cut();
+                self.last_rotation = kick;
+            } else if moved {
+                self.last_rotation = None;
+            }
+            if moved || rotated {
+                self.lock_delay.on_move();
+                // A wall kick can push the piece down as well.
+                self.lock_delay.on_fall(piece.y());
+            }
+        }
+
+        if pressed.hard_drop {
+            let rows = self.drop_to_bottom();
+            self.tetris.score = self.tetris.score.saturating_add(rows * HARD_DROP_POINTS);
+            self.lock(&mut events);
+            return events;
+        }
+        if pressed.sonic_drop {
+            self.drop_to_bottom();
+        }
+
+        // The gravity piles up every frame and the piece goes down by one row
+        // for every whole row it adds up to: above 1G, it's several rows per frame.
+        // The soft drop still works when the gravity is turned off.
+        let gravity = if inputs.soft_drop {
+            (self.config.gravity.gravity(self.tetris.current_level) * SOFT_DROP_FACTOR).min(TWENTY_G)
+        } else {
+            self.gravity()
+        };
+        self.gravity_counter += gravity;
+        while self.gravity_counter >= ONE_G {
+            self.gravity_counter -= ONE_G;
+            if self.fall(&mut events, inputs.soft_drop) {
+                return events;
+            }
+            // What is left can't make the piece go through the floor.
+            if self.on_ground() {
+                self.gravity_counter = 0;
+            }
+        }
+
+        let on_ground = self.on_ground();
+        if self.lock_delay.tick(on_ground) {
+            self.lock(&mut events);
+        }
+        events
+    }
+
+    // Whether the current tetrimino can't go any lower.
+    fn on_ground(&self) -> bool {
+        match self.tetris.current_piece {
+            Some(ref piece) => piece.landing_y(&self.tetris.game_map) == piece.y(),
+            None => false,
+        }
+    }
+
+    // Moves the current tetrimino down by one row and returns whether it locked.
+    // When the player made it go down with the soft drop, they earn a few points.
+    //
+    // A piece which can't go any lower only locks here with the classic lock,
+    // otherwise the lock delay takes care of it.
+    fn fall(&mut self, events: &mut Vec<GameEvent>, soft_drop: bool) -> bool {
+        let Tetris { ref game_map, ref mut current_piece, .. } = self.tetris;
+        if let Some(piece) = current_piece {
+            let (x, y) = (piece.x(), piece.y());
+            if piece.change_position(game_map, x, y + piece.scale() as isize) {
+                self.lock_delay.on_fall(piece.y());
+                self.last_rotation = None;
+                if soft_drop {
+                    self.tetris.score = self.tetris.score.saturating_add(SOFT_DROP_POINTS);
+                }
+                return false;
+            }
+        }
+        if self.lock_delay.is_classic() {
+            self.lock(events);
+            return true;
+        }
+        false
+    }
+
+    // Moves the current tetrimino straight to where it would land
+    // and returns by how many rows it went down (rows of big blocks in big mode).
+    fn drop_to_bottom(&mut self) -> u32 {
+        let Tetris { ref game_map, ref mut current_piece, .. } = self.tetris;
+        match current_piece {
+            Some(piece) => {
+                let (x, y) = (piece.x(), piece.y());
+                let landing_y = piece.landing_y(game_map);
+                piece.change_position(game_map, x, landing_y);
+                self.lock_delay.on_fall(landing_y);
+                if landing_y > y {
+                    self.last_rotation = None;
+                }
+                ((landing_y - y) / piece.scale() as isize) as u32
+            }
+            None => 0,
+        }
+    }
+
+    // The current tetrimino becomes part of the game map,
+    // the full lines are removed and the next piece comes in.
+    //
+    // The T-spin has to be checked before the piece is written into the map,
+    // otherwise its own blocks would be in the way. The 3-corner rule only works
+    // with blocks of one cell, so there are no T-spins in big mode.
+    fn lock(&mut self, events: &mut Vec<GameEvent>) {
+        let scale = self.config.scale();
+        let t_spin = match self.tetris.current_piece {
+            Some(ref piece) if scale == 1 => detect_t_spin(piece,
+                                                           &self.tetris.game_map,
+                                                           self.last_rotation.take(),
+                                                           &*self.config.rotation_system),
+            _ => TSpin::None,
+        };
+        if let Some(turn_start) = self.turn_start.take() {
+            // The oldest placement is forgotten first.
+            if self.undo_stack.len() == MAX_UNDO {
+                self.undo_stack.pop_front();
+            }
+            self.undo_stack.push_back(turn_start);
+            self.redo_stack.clear();
+        }
+        self.tetris.make_permanent();
+        events.push(GameEvent::PieceLocked);
+        let game_map = &self.tetris.game_map;
+        self.cleared_rows = game_map.full_lines(scale);
+        let garbage_rows = self.garbage_left as usize * scale;
+        let garbage_lines = game_map.height().saturating_sub(garbage_rows)..game_map.height();
+        let garbage_cleared = self.cleared_rows.iter().filter(|&y| garbage_lines.contains(y)).count() / scale;
+        let garbage_cleared = garbage_cleared as u32;
+        self.garbage_left -= garbage_cleared;
+        self.garbage_cleared += garbage_cleared;
+        let lines = self.tetris.check_lines(scale);
+        if lines > 0 {
+            events.push(GameEvent::LinesCleared(lines));
+        }
+        let perfect_clear = lines > 0 && self.tetris.game_map.is_empty();
+        if let Some(clear) = self.scoring.lock(lines, t_spin, perfect_clear, self.tetris.current_level) {
+            self.tetris.score = self.tetris.score.saturating_add(clear.points);
+            events.push(GameEvent::Scored(clear));
+            let level = self.levels.on_clear(self.tetris.current_level, &clear);
+            if level > self.tetris.current_level {
+                self.tetris.current_level = level;
+                events.push(GameEvent::LevelUp(level));
+            }
+        }
+        if lines > 0 {
+            while self.splits.len() < (self.tetris.nb_lines / SPLIT_LINES) as usize {
+                let split = self.stopwatch.elapsed();
+                self.splits.push(split);
+                events.push(GameEvent::Split(split));
+            }
+            let goal_reached = match self.config.mode {
+                GameMode::Dig { rise: None, .. } => self.garbage_left == 0,
+                mode => match mode.line_goal() {
+                    Some(goal) => self.tetris.nb_lines >= goal,
+                    None => false,
+                },
+            };
+            if goal_reached {
+                self.complete(events);
+                return;
+            }
+        }
+        self.hold_used = false;
+        self.spawn(events);
+        self.start_turn();
+    }
+
+    // Remembers the state of the game as the new piece comes in, for the undo of the zen mode.
+    fn start_turn(&mut self) {
+        if self.config.mode != GameMode::Zen {
+            return;
+        }
+        let piece = match self.tetris.current_piece {
+            Some(ref piece) => piece.kind(),
+            None => return,
+        };
+        self.turn_start = Some(Snapshot {
+            game_map: self.tetris.game_map.clone(),
+            piece,
+            level: self.tetris.current_level,
+            score: self.tetris.score,
+            nb_lines: self.tetris.nb_lines,
+            rng: self.rng.clone(),
+            queue: self.queue.clone(),
+            held_piece: self.held_piece,
+            hold_used: self.hold_used,
+            scoring: self.scoring.clone(),
+            levels: self.levels.clone(),
+        });
+    }
+
+    // Puts the game back in the given state, with its piece coming in again from the top.
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.turn_start = Some(snapshot.clone());
+        let Snapshot { game_map, piece, level, score, nb_lines, rng, queue, held_piece, hold_used, scoring, levels } =
+            snapshot;
+        self.tetris.game_map = game_map;
+        self.tetris.current_level = level;
+        self.tetris.score = score;
+        self.tetris.nb_lines = nb_lines;
+        self.rng = rng;
+        self.queue = queue;
+        self.held_piece = held_piece;
+        self.hold_used = hold_used;
+        self.scoring = scoring;
+        self.levels = levels;
+        self.gravity_counter = 0;
+        self.spawn_piece(piece, &mut Vec::new());
+    }
+
+    // The player reached the goal of the mode: the game ends there.
+    fn complete(&mut self, events: &mut Vec<GameEvent>) {
+        self.stopwatch.stop();
+        self.completed = true;
+        events.push(GameEvent::Completed);
+    }
+
+    // Brings a new garbage line up from the bottom of the game map
+    // (a pair of lines with a hole of two columns in big mode).
+    // If this pushes blocks out of the top of the map, or the current piece can't make room
+    // for it by going up, the game is over.
+    fn add_garbage(&mut self, events: &mut Vec<GameEvent>) {
+        let scale = self.config.scale();
+        let hole = self.garbage.next_hole(self.tetris.game_map.width() / scale) * scale;
+        let mut fits = true;
+        for _ in 0..scale {
+            fits &= self.tetris.game_map.push_line(hole..hole + scale, GARBAGE_COLOR);
+        }
+        self.garbage_left += 1;
+        let Tetris { ref game_map, ref mut current_piece, .. } = self.tetris;
+        if let Some(piece) = current_piece {
+            if !piece.is_valid(game_map) {
+                let (x, y) = (piece.x(), piece.y());
+                fits &= piece.change_position(game_map, x, y - scale as isize);
+            }
+        }
+        if !fits {
+            self.game_over = true;
+            self.stopwatch.stop();
+            events.push(GameEvent::GameOver);
+        }
+    }
+
+    // Puts the current tetrimino aside and brings back the one which was held before.
+    // If nothing was held yet, the next piece of the queue comes in instead.
+    // Either way, the new piece starts from the top, in its spawn state.
+    fn hold(&mut self, events: &mut Vec<GameEvent>) {
+        if self.hold_used {
+            return;
+        }
+        let current = match self.tetris.current_piece.take() {
+            Some(piece) => piece.kind(),
+            None => return,
+        };
+        self.hold_used = true;
+        events.push(GameEvent::PieceHeld);
+        self.gravity_counter = 0;
+        match self.held_piece.replace(current) {
+            Some(kind) => self.spawn_piece(kind, events),
+            None => self.spawn(events),
+        }
+    }
+
+    // Brings the next tetrimino at the top of the map.
+    fn spawn(&mut self, events: &mut Vec<GameEvent>) {
+        let kind = self.queue.pop(&mut self.rng);
+        self.spawn_piece(kind, events);
+    }
+
+    // Puts a new tetrimino of the given kind at the top of the map.
+    // If it doesn't fit there, the stack is too high and the game is over.
+    // The zen mode never ends: the game map is emptied instead.
+    fn spawn_piece(&mut self, kind: PieceKind, events: &mut Vec<GameEvent>) {
+        let mut piece = Tetrimino::with_scale(kind, &*self.config.rotation_system, self.config.scale());
+        piece.move_to_spawn(self.config.width, self.config.hidden_rows);
+        if self.config.mode == GameMode::Zen && !piece.is_valid(&self.tetris.game_map) {
+            let game_map = &self.tetris.game_map;
+            self.tetris.game_map = Board::new(game_map.width(), game_map.height());
+        }
+        if piece.is_valid(&self.tetris.game_map) {
+            // Like in the guideline, the piece goes down by one row right away if it can,
+            // so it can be seen at the top of the visible part of the game map.
+            let (x, y) = (piece.x(), piece.y());
+            piece.change_position(&self.tetris.game_map, x, y + piece.scale() as isize);
+            self.lock_delay.new_piece(piece.y());
+            self.last_rotation = None;
+            self.auto_shift.cut();
+            self.tetris.current_piece = Some(piece);
+            // At 20G, there is no time to see the piece at the top: it's already on the floor.
+            if self.gravity() >= TWENTY_G {
+                self.drop_to_bottom();
+            }
+            events.push(GameEvent::PieceSpawned);
+        } else {
+            self.game_over = true;
+            self.stopwatch.stop();
+            events.push(GameEvent::GameOver);
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Game {
+        Game::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zen() -> Game {
+        Game::with_config(GameConfig { mode: GameMode::Zen, seed: 7, ..GameConfig::default() })
+    }
+
+    // Presses the hard drop (letting go of it first, so it counts as pressed).
+    fn hard_drop(game: &mut Game) -> Vec<GameEvent> {
+        game.step(Inputs::default());
+        game.step(Inputs { hard_drop: true, ..Inputs::default() })
+    }
+
+    fn current_kind(game: &Game) -> PieceKind {
+        game.tetris().current_piece.as_ref().expect("there is always a piece in zen").kind()
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut game = zen();
+        let first_piece = current_kind(&game);
+        let first_queue = game.upcoming_pieces().collect::<Vec<_>>();
+        hard_drop(&mut game);
+        let second_piece = current_kind(&game);
+        let second_map = game.tetris().game_map.clone();
+        let second_score = game.tetris().score;
+        hard_drop(&mut game);
+        let third_map = game.tetris().game_map.clone();
+        let third_queue = game.upcoming_pieces().collect::<Vec<_>>();
+        assert_eq!(game.undo_count(), 2);
+
+        assert!(game.undo());
+        assert_eq!(game.tetris().game_map, second_map);
+        assert_eq!(game.tetris().score, second_score);
+        assert_eq!(current_kind(&game), second_piece);
+        assert!(game.undo());
+        assert!(game.tetris().game_map.is_empty());
+        assert_eq!(game.tetris().score, 0);
+        assert_eq!(current_kind(&game), first_piece);
+        assert_eq!(game.upcoming_pieces().collect::<Vec<_>>(), first_queue);
+        assert!(!game.undo());
+        assert_eq!(game.redo_count(), 2);
+
+        assert!(game.redo());
+        assert!(game.redo());
+        assert!(!game.redo());
+        assert_eq!(game.tetris().game_map, third_map);
+        assert_eq!(game.upcoming_pieces().collect::<Vec<_>>(), third_queue);
+    }
+
+    #[test]
+    fn placing_a_piece_forgets_the_redo() {
+        let mut game = zen();
+        hard_drop(&mut game);
+        hard_drop(&mut game);
+        let queue = game.upcoming_pieces().collect::<Vec<_>>();
+        game.undo();
+        // The same piece comes again, from the same randomizer state.
+        hard_drop(&mut game);
+        assert_eq!(game.upcoming_pieces().collect::<Vec<_>>(), queue);
+        assert_eq!(game.redo_count(), 0);
+        assert!(!game.redo());
+    }
+
+    #[test]
+    fn choose_the_next_piece() {
+        let mut game = zen();
+        assert!(game.choose_next_piece(PieceKind::I));
+        assert_eq!(game.upcoming_pieces().next(), Some(PieceKind::I));
+        hard_drop(&mut game);
+        assert_eq!(current_kind(&game), PieceKind::I);
+        // Going back keeps the choice.
+        game.undo();
+        assert_eq!(game.upcoming_pieces().next(), Some(PieceKind::I));
+        // Only in zen.
+        let mut game = Game::with_config(GameConfig { seed: 7, ..GameConfig::default() });
+        assert!(!game.choose_next_piece(PieceKind::I));
+        hard_drop(&mut game);
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn no_top_out_and_limited_history() {
+        let mut game = zen();
+        for _ in 0..MAX_UNDO + 10 {
+            let events = hard_drop(&mut game);
+            assert!(!events.contains(&GameEvent::GameOver));
+        }
+        assert!(!game.is_game_over());
+        assert_eq!(game.undo_count(), MAX_UNDO);
+    }
+
+    #[test]
+    fn gravity_can_be_turned_off() {
+        let mut game = zen();
+        assert!(!game.toggle_gravity());
+        assert_eq!(game.gravity(), 0);
+        let y = game.tetris().current_piece.as_ref().map(|piece| piece.y());
+        for _ in 0..1000 {
+            game.step(Inputs::default());
+        }
+        assert_eq!(game.tetris().current_piece.as_ref().map(|piece| piece.y()), y);
+        assert!(game.toggle_gravity());
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let play = || {
+            let mut game = Game::with_config(GameConfig { seed: 42, ..GameConfig::default() });
+            for frame in 0..2000 {
+                game.step(Inputs {
+                    left: frame % 7 < 2,
+                    right: frame % 11 < 3,
+                    rotate_cw: frame % 5 == 0,
+                    hard_drop: frame % 30 == 0,
+                    ..Inputs::default()
+                });
+            }
+            let tetris = game.tetris();
+            (tetris.game_map.clone(), tetris.score, tetris.nb_lines, game.upcoming_pieces().collect::<Vec<_>>())
+        };
+        let first = play();
+        // The pieces did go somewhere, otherwise there would be nothing to compare.
+        assert!(first.1 > 0);
+        assert_eq!(first, play());
+    }
+
+    fn big(width: usize, pieces: Vec<PieceKind>) -> Game {
+        Game::with_config(GameConfig { big: true,
+                                       width,
+                                       randomizer: crate::randomizer::RandomizerKind::Sequence(pieces),
+                                       seed: 7,
+                                       ..GameConfig::default() })
+    }
+
+    #[test]
+    fn big_pieces_move_by_two() {
+        let mut game = big(10, vec![PieceKind::T]);
+        let piece = game.tetris().current_piece.as_ref().expect("the first piece fits");
+        let (x, y) = (piece.x(), piece.y());
+        assert_eq!((x % 2, y % 2), (0, 0));
+        game.step(Inputs { left: true, ..Inputs::default() });
+        let piece = game.tetris().current_piece.as_ref().expect("the piece is still there");
+        assert_eq!(piece.x(), x - 2);
+        assert_eq!((game.ghost_y().expect("the ghost is on") - piece.y()) % 2, 0);
+    }
+
+    #[test]
+    fn big_lines_go_by_pairs() {
+        // A big I lying down fills both lines of a pair in an 8-wide game map.
+        let mut game = big(8, vec![PieceKind::I]);
+        let events = hard_drop(&mut game);
+        assert!(events.contains(&GameEvent::LinesCleared(1)));
+        assert_eq!(game.tetris().nb_lines, 1);
+        let height = game.tetris().game_map.height();
+        assert_eq!(game.cleared_rows(), &[height - 2, height - 1]);
+        assert!(game.tetris().game_map.is_empty());
+    }
+
+    #[test]
+    fn big_garbage_comes_by_pairs() {
+        let game = Game::with_config(GameConfig { big: true,
+                                                  mode: GameMode::Dig { rows: 3,
+                                                                        garbage: crate::garbage::Garbage::Messy,
+                                                                        rise: None },
+                                                  seed: 7,
+                                                  ..GameConfig::default() });
+        assert_eq!(game.garbage_left(), 3);
+        let game_map = &game.tetris().game_map;
+        let height = game_map.height();
+        for y in (height - 6..height).step_by(2) {
+            assert_eq!(game_map.row(y), game_map.row(y + 1));
+            assert_eq!(game_map.row(y).count_zeros(), 16 - 10 + 2);
+        }
+        assert_eq!(game_map.row(height - 7), 0);
+    }
+}
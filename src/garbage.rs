@@ -0,0 +1,50 @@
+use rand::Rng;
+
+use crate::rng::{rng_from_seed, GameRng};
+
+// The color id of the garbage blocks, after the ones of the seven tetriminos.
+pub const GARBAGE_COLOR: u8 = 8;
+
+// The garbage doesn't use the random numbers of the pieces: otherwise, a row rising
+// one frame earlier or later would change every piece coming after it.
+// It gets its own generator instead, seeded from the seed of the game mixed with this,
+// so two players racing on the same seed get the same pieces and the same garbage.
+const GARBAGE_SEED: u64 = 0x6761_7262_6167_6521;
+
+// Where the holes of the garbage rows go.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Garbage {
+    // Every row has its hole somewhere else than the row before: digging takes a lot of work.
+    #[default]
+    Messy,
+    // Every row has its hole in the same column: a few I pieces go a long way.
+    Clean,
+}
+
+// Gives the column of the hole of every new garbage row.
+pub(crate) struct GarbageGenerator {
+    garbage: Garbage,
+    rng: GameRng,
+    last_hole: Option<usize>,
+}
+
+impl GarbageGenerator {
+    pub(crate) fn new(garbage: Garbage, seed: u64) -> GarbageGenerator {
+        GarbageGenerator { garbage, rng: rng_from_seed(seed ^ GARBAGE_SEED), last_hole: None }
+    }
+
+    pub(crate) fn next_hole(&mut self, width: usize) -> usize {
+        let hole = match (self.garbage, self.last_hole) {
+            (Garbage::Clean, Some(hole)) => hole,
+            // Picking among the other columns, then skipping the last hole,
+            // gives every other column the same chance.
+            (Garbage::Messy, Some(last_hole)) if width > 1 => {
+                let hole = self.rng.gen_range(0, width - 1);
+                if hole >= last_hole { hole + 1 } else { hole }
+            }
+            _ => self.rng.gen_range(0, width),
+        };
+        self.last_hole = Some(hole);
+        hole
+    }
+}
@@ -0,0 +1,237 @@
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+// How many scores we keep.
+pub const NB_HIGHSCORES: usize = 5;
+
+const HIGHSCORES_FILE: &str = "scores.txt";
+
+// One line of the high scores table. The seed is kept along with the score,
+// so anyone can play the very same game again and try to beat it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub score: u32,
+    pub lines: u32,
+    pub seed: u64,
+}
+
+impl HighScore {
+    fn to_line(self) -> String {
+        format!("{} {} {}", self.score, self.lines, self.seed)
+    }
+
+    // A line is made of the score, the number of lines and the seed, separated by spaces.
+    // Anything else (like the scores files of older versions) is just ignored.
+    fn from_line(line: &str) -> Option<HighScore> {
+        let mut values = line.split(' ');
+        let highscore = HighScore {
+            score: values.next()?.parse().ok()?,
+            lines: values.next()?.parse().ok()?,
+            seed: values.next()?.parse().ok()?,
+        };
+        if values.next().is_some() {
+            return None;
+        }
+        Some(highscore)
+    }
+}
+
+fn highscores_to_string(highscores: &[HighScore]) -> String {
+    highscores
+        // Here we create an iterator from our slice
+        // A really important and fundamental thing to note about iterators is in Rust;
+        // they're lazy.
+        // Create an iterator doesn't cost anything more than the size of type.
+        // (generally a structure containing a pointer and an index).
+        // Until the next() method is called, nothing happens.
+        .iter()
+        // We call the iterator's map method.
+        // What it does is simple: it converts the current type into another one.
+        // Really important to note: at this point, the iterator still hasn't done anything/
+        // Keep in mid that nothing is done util the next() method is called.
+        .map(|highscore| highscore.to_line())
+        // And now we call the collect() method.
+        // It'll call the next() method of our iterator as long as
+        // it didn't get all elements and store them into a Vec.
+        // This is where map() method will be called on every element of out iterator
+        .collect::<Vec<String>>()
+        // And finally the last step: This method (as its name indicates) joins all the elements
+        // of the Vec into a String separated by the given &str (so, "\n" in our case).
+        .join("\n")
+}
+
+pub fn save_highscores(highscores: &[HighScore]) -> bool {
+    // The tables of the other modes are in the same file: we keep them as they are.
+    let mut content = other_lines(|line| HighScore::from_line(line).is_some());
+    content.push_str(&highscores_to_string(highscores));
+    content.push('\n');
+    // The is_ok() method call just informs the caller of the save_highscores() function
+    // if everything has been saved as expected or not.
+    write_into_file(&content, HIGHSCORES_FILE).is_ok()
+}
+
+fn write_into_file(content: &str, filename: &str) -> io::Result<()> {
+    // try! marco can be replaced with ? operator
+    let mut f = File::create(filename)?;
+    f.write_all(content.as_bytes())
+}
+
+// Returns the saved high scores, the best one first.
+// If there is no file yet, well, there is no high score yet either.
+pub fn load_highscores() -> Vec<HighScore> {
+    if let Ok(content) = read_from_file(HIGHSCORES_FILE) {
+        content.lines().filter_map(HighScore::from_line).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+// Puts a new score in the table if it's good enough to be in it.
+// Returns whether it made it.
+pub fn insert_highscore(highscores: &mut Vec<HighScore>, highscore: HighScore) -> bool {
+    let position = highscores
+        .iter()
+        .position(|other| other.score < highscore.score)
+        .unwrap_or(highscores.len());
+    if position >= NB_HIGHSCORES {
+        return false;
+    }
+    highscores.insert(position, highscore);
+    highscores.truncate(NB_HIGHSCORES);
+    true
+}
+
+// The best time of a sprint. Every line goal has its own table, in the same file
+// as the high scores: its lines look like `sprint:40 83456 1234`, with the time
+// in milliseconds and the seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SprintRecord {
+    pub time: Duration,
+    pub seed: u64,
+}
+
+impl SprintRecord {
+    fn to_line(self, goal: u32) -> String {
+        format!("sprint:{} {} {}", goal, self.time.as_millis(), self.seed)
+    }
+
+    // Only reads the lines of the given goal.
+    fn from_line(line: &str, goal: u32) -> Option<SprintRecord> {
+        let mut values = line.split(' ');
+        if values.next()? != format!("sprint:{}", goal) {
+            return None;
+        }
+        let record = SprintRecord {
+            time: Duration::from_millis(values.next()?.parse().ok()?),
+            seed: values.next()?.parse().ok()?,
+        };
+        if values.next().is_some() {
+            return None;
+        }
+        Some(record)
+    }
+}
+
+// Returns the best times of the given sprint goal, the fastest first.
+pub fn load_sprint_records(goal: u32) -> Vec<SprintRecord> {
+    if let Ok(content) = read_from_file(HIGHSCORES_FILE) {
+        content.lines().filter_map(|line| SprintRecord::from_line(line, goal)).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+// Puts a new time in the table if it's fast enough to be in it.
+// Returns whether it made it.
+pub fn insert_sprint_record(records: &mut Vec<SprintRecord>, record: SprintRecord) -> bool {
+    let position = records
+        .iter()
+        .position(|other| other.time > record.time)
+        .unwrap_or(records.len());
+    if position >= NB_HIGHSCORES {
+        return false;
+    }
+    records.insert(position, record);
+    records.truncate(NB_HIGHSCORES);
+    true
+}
+
+// Saves the best times of the given sprint goal, leaving everything else in the file alone.
+pub fn save_sprint_records(goal: u32, records: &[SprintRecord]) -> bool {
+    let mut content = other_lines(|line| SprintRecord::from_line(line, goal).is_some());
+    for record in records {
+        content.push_str(&record.to_line(goal));
+        content.push('\n');
+    }
+    write_into_file(&content, HIGHSCORES_FILE).is_ok()
+}
+
+// The high scores of the ultra and marathon modes are kept apart from the endless ones,
+// since they're not about the same thing at all. Every duration of the ultra and every
+// line goal of the marathon has its own table, named like `ultra:120` or `marathon:150`:
+// its lines look like the ones of the high scores, after the name of the table.
+fn table_score_from_line(line: &str, table: &str) -> Option<HighScore> {
+    let prefix = format!("{} ", table);
+    if line.starts_with(&prefix) {
+        HighScore::from_line(&line[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+fn load_table_highscores(table: &str) -> Vec<HighScore> {
+    if let Ok(content) = read_from_file(HIGHSCORES_FILE) {
+        content.lines().filter_map(|line| table_score_from_line(line, table)).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+fn save_table_highscores(table: &str, highscores: &[HighScore]) -> bool {
+    let mut content = other_lines(|line| table_score_from_line(line, table).is_some());
+    for highscore in highscores {
+        content.push_str(&format!("{} {}\n", table, highscore.to_line()));
+    }
+    write_into_file(&content, HIGHSCORES_FILE).is_ok()
+}
+
+// Returns the high scores of the ultra mode lasting the given number of seconds,
+// the best one first. `insert_highscore` works for them as well.
+pub fn load_ultra_highscores(seconds: u64) -> Vec<HighScore> {
+    load_table_highscores(&format!("ultra:{}", seconds))
+}
+
+pub fn save_ultra_highscores(seconds: u64, highscores: &[HighScore]) -> bool {
+    save_table_highscores(&format!("ultra:{}", seconds), highscores)
+}
+
+// The same for the marathon going to the given number of lines.
+pub fn load_marathon_highscores(lines: u32) -> Vec<HighScore> {
+    load_table_highscores(&format!("marathon:{}", lines))
+}
+
+pub fn save_marathon_highscores(lines: u32, highscores: &[HighScore]) -> bool {
+    save_table_highscores(&format!("marathon:{}", lines), highscores)
+}
+
+// Several tables share the scores file, so saving one of them must keep the others.
+// This returns every line of the file which isn't part of the table being saved.
+fn other_lines<F: Fn(&str) -> bool>(is_part_of_table: F) -> String {
+    read_from_file(HIGHSCORES_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !is_part_of_table(line))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+// This time, it only tales a filename as an argument
+// and returns a String if the reading was successful.
+fn read_from_file(filename: &str) -> io::Result<String> {
+    let mut f = File::open(filename)?;
+    let mut content = String::new();
+    f.read_to_string(&mut content)?;
+    Ok(content)
+}
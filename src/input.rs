@@ -0,0 +1,263 @@
+use std::str::FromStr;
+
+// The engine runs at a fixed rate: one call to `Game::step` is one frame.
+pub const FRAMES_PER_SECOND: u32 = 60;
+
+// A duration, given either in frames or in milliseconds.
+// Players coming from different games are used to one or the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Timing {
+    Frames(u32),
+    Millis(u32),
+}
+
+impl Timing {
+    // Milliseconds are rounded to the closest frame. The math is done on 64 bits,
+    // since a big number of milliseconds times the frame rate doesn't fit in a `u32`.
+    pub fn to_frames(self) -> u32 {
+        match self {
+            Timing::Frames(frames) => frames,
+            Timing::Millis(millis) => {
+                let frames = (millis as u64 * FRAMES_PER_SECOND as u64 + 500) / 1000;
+                frames.min(u32::MAX as u64) as u32
+            }
+        }
+    }
+}
+
+// "10" means 10 frames, "160ms" means 160 milliseconds.
+impl FromStr for Timing {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Timing, Self::Err> {
+        if let Some(millis) = s.strip_suffix("ms") {
+            Ok(Timing::Millis(millis.parse()?))
+        } else {
+            Ok(Timing::Frames(s.parse()?))
+        }
+    }
+}
+
+// How the piece moves when the player holds left or right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Handling {
+    // Delayed Auto Shift: how long the key has to be held before the piece starts moving by itself.
+    pub das: Timing,
+    // Auto Repeat Rate: how long between two moves once it started. Zero sends the piece
+    // straight to the wall.
+    pub arr: Timing,
+    // After a rotation or a new piece, the auto shift waits this long before moving again,
+    // so the new piece doesn't fly away before the player even saw it.
+    pub das_cut: Timing,
+}
+
+impl Default for Handling {
+    fn default() -> Handling {
+        Handling {
+            das: Timing::Frames(10),
+            arr: Timing::Frames(2),
+            das_cut: Timing::Frames(1),
+        }
+    }
+}
+
+// The buttons the player is holding during a frame.
+// The frontend fills it however it wants (keyboard, gamepad, a bot...),
+// the engine then figures out by itself which ones have just been pressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Inputs {
+    pub left: bool,
+    pub right: bool,
+    // Makes the gravity faster for as long as it's held.
+    pub soft_drop: bool,
+    // Drops the piece to the bottom and locks it right away.
+    pub hard_drop: bool,
+    // Drops the piece to the bottom but lets the player still move it.
+    pub sonic_drop: bool,
+    pub rotate_cw: bool,
+    pub rotate_ccw: bool,
+    pub hold: bool,
+}
+
+impl Inputs {
+    // The buttons held now which weren't during the previous frame.
+    pub(crate) fn pressed_since(self, previous: Inputs) -> Inputs {
+        Inputs {
+            left: self.left && !previous.left,
+            right: self.right && !previous.right,
+            soft_drop: self.soft_drop && !previous.soft_drop,
+            hard_drop: self.hard_drop && !previous.hard_drop,
+            sonic_drop: self.sonic_drop && !previous.sonic_drop,
+            rotate_cw: self.rotate_cw && !previous.rotate_cw,
+            rotate_ccw: self.rotate_ccw && !previous.rotate_ccw,
+            hold: self.hold && !previous.hold,
+        }
+    }
+}
+
+// What the auto shift wants the piece to do during a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Shift {
+    Stay,
+    // Move by `steps` cells towards `direction` (-1 for left, 1 for right),
+    // or as far as possible if it can't go that far.
+    Move { direction: isize, steps: u32 },
+}
+
+// Keeps track of how long left or right has been held and turns it into moves.
+pub(crate) struct AutoShift {
+    das: u32,
+    arr: u32,
+    das_cut: u32,
+    // -1 for left, 1 for right, 0 when neither is held.
+    direction: isize,
+    // How many frames the direction has been held.
+    charge: u32,
+    repeat_timer: u32,
+    cut_timer: u32,
+}
+
+impl AutoShift {
+    pub(crate) fn new(handling: Handling) -> AutoShift {
+        AutoShift {
+            das: handling.das.to_frames(),
+            arr: handling.arr.to_frames(),
+            das_cut: handling.das_cut.to_frames(),
+            direction: 0,
+            charge: 0,
+            repeat_timer: 0,
+            cut_timer: 0,
+        }
+    }
+
+    // Called after a rotation or when a new piece comes in.
+    pub(crate) fn cut(&mut self) {
+        self.cut_timer = self.das_cut;
+    }
+
+    pub(crate) fn update(&mut self, inputs: Inputs, pressed: Inputs) -> Shift {
+        let cut = self.cut_timer > 0;
+        if cut {
+            self.cut_timer -= 1;
+        }
+
+        // When both keys are held, the last one pressed wins.
+        let direction = if pressed.left != pressed.right {
+            if pressed.left { -1 } else { 1 }
+        } else if (self.direction == -1 && inputs.left) || (self.direction == 1 && inputs.right) {
+            self.direction
+        } else if inputs.left != inputs.right {
+            if inputs.left { -1 } else { 1 }
+        } else {
+            0
+        };
+
+        if direction == 0 {
+            self.direction = 0;
+            self.charge = 0;
+            return Shift::Stay;
+        }
+        // A new direction always moves the piece once, right away.
+        if direction != self.direction {
+            self.direction = direction;
+            self.charge = 0;
+            self.repeat_timer = 0;
+            return Shift::Move { direction, steps: 1 };
+        }
+
+        self.charge = self.charge.saturating_add(1);
+        if cut || self.charge < self.das {
+            return Shift::Stay;
+        }
+        if self.arr == 0 {
+            return Shift::Move { direction, steps: u32::MAX };
+        }
+        // The first repeat happens as soon as the delay is over, the next ones every `arr` frames.
+        if self.charge == self.das {
+            self.repeat_timer = 0;
+            return Shift::Move { direction, steps: 1 };
+        }
+        self.repeat_timer += 1;
+        if self.repeat_timer >= self.arr {
+            self.repeat_timer = 0;
+            Shift::Move { direction, steps: 1 }
+        } else {
+            Shift::Stay
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handling(das: u32, arr: u32, das_cut: u32) -> Handling {
+        Handling { das: Timing::Frames(das), arr: Timing::Frames(arr), das_cut: Timing::Frames(das_cut) }
+    }
+
+    // Holds the given buttons for that many frames and returns on which frames the piece moved.
+    fn hold(auto_shift: &mut AutoShift, previous: &mut Inputs, inputs: Inputs, frames: u32) -> Vec<(u32, Shift)> {
+        let mut moves = Vec::new();
+        for frame in 0..frames {
+            let shift = auto_shift.update(inputs, inputs.pressed_since(*previous));
+            *previous = inputs;
+            if shift != Shift::Stay {
+                moves.push((frame, shift));
+            }
+        }
+        moves
+    }
+
+    const LEFT: Inputs = Inputs { left: true, right: false, soft_drop: false, hard_drop: false, sonic_drop: false,
+                                  rotate_cw: false, rotate_ccw: false, hold: false };
+    const BOTH: Inputs = Inputs { right: true, ..LEFT };
+
+    #[test]
+    fn delay_then_repeat() {
+        let mut auto_shift = AutoShift::new(handling(10, 2, 0));
+        let moves = hold(&mut auto_shift, &mut Inputs::default(), LEFT, 15);
+        let one = Shift::Move { direction: -1, steps: 1 };
+        assert_eq!(moves, vec![(0, one), (10, one), (12, one), (14, one)]);
+    }
+
+    #[test]
+    fn zero_arr_goes_to_the_wall() {
+        let mut auto_shift = AutoShift::new(handling(3, 0, 0));
+        let moves = hold(&mut auto_shift, &mut Inputs::default(), LEFT, 5);
+        assert_eq!(moves[0], (0, Shift::Move { direction: -1, steps: 1 }));
+        assert_eq!(moves[1], (3, Shift::Move { direction: -1, steps: u32::MAX }));
+    }
+
+    #[test]
+    fn cut_holds_the_charged_shift_back() {
+        let mut auto_shift = AutoShift::new(handling(2, 1, 3));
+        let mut previous = Inputs::default();
+        assert_eq!(hold(&mut auto_shift, &mut previous, LEFT, 3).len(), 2);
+        // A new piece comes in: nothing happens for 3 frames, then the repeat starts again.
+        auto_shift.cut();
+        let moves = hold(&mut auto_shift, &mut previous, LEFT, 5);
+        assert_eq!(moves.iter().map(|&(frame, _)| frame).collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[test]
+    fn last_pressed_wins() {
+        let mut auto_shift = AutoShift::new(handling(10, 2, 0));
+        let mut previous = Inputs::default();
+        hold(&mut auto_shift, &mut previous, LEFT, 5);
+        let moves = hold(&mut auto_shift, &mut previous, BOTH, 11);
+        let right = Shift::Move { direction: 1, steps: 1 };
+        // Right starts charging from zero.
+        assert_eq!(moves, vec![(0, right), (10, right)]);
+        // Letting go of right goes back to left, which moves right away.
+        let moves = hold(&mut auto_shift, &mut previous, LEFT, 1);
+        assert_eq!(moves, vec![(0, Shift::Move { direction: -1, steps: 1 })]);
+    }
+
+    #[test]
+    fn milliseconds_to_frames() {
+        assert_eq!("160ms".parse::<Timing>().map(Timing::to_frames), Ok(10));
+        assert_eq!("7".parse::<Timing>().map(Timing::to_frames), Ok(7));
+        assert_eq!(Timing::Millis(80_000_000).to_frames(), 4_800_000);
+        assert_eq!(Timing::Millis(u32::MAX).to_frames(), 257_698_038);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::input::FRAMES_PER_SECOND;
+use crate::scoring::Clear;
+
+// The gravity is counted in 1/65536 of a row per frame, so the slow levels
+// don't get rounded too much. A gravity of `ONE_G` moves the piece by one row every frame.
+pub const ONE_G: u32 = 65536;
+// The fastest a piece can fall: it goes straight to the bottom the very frame it appears.
+// The usual game map is 20 rows high, so 20 rows per frame is as good as infinite.
+pub const TWENTY_G: u32 = 20 * ONE_G;
+
+// The highest level a game can start at. The gravity doesn't change past level 30
+// on any curve, so this is only there to keep the points (multiplied by the level) sane.
+pub const MAX_START_LEVEL: u32 = 999;
+
+// How many frames a piece waits before going down by one row on the NES (NTSC),
+// from level 0 to 29. Every level after that uses the last value.
+const NES_FRAMES_PER_ROW: [u32; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6,
+    5, 5, 5, 4, 4, 4, 3, 3, 3, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 1,
+];
+
+// The internal gravity of TGM, in 1/256 of a row per frame: each pair is the TGM level
+// from which the gravity applies and the gravity itself. Yes, it slows down at 200.
+const TGM_GRAVITY: [(u32, u32); 30] = [
+    (0, 4), (30, 6), (35, 8), (40, 10), (50, 12), (60, 16), (70, 32), (80, 48),
+    (90, 64), (100, 80), (120, 96), (140, 112), (160, 128), (170, 144), (200, 4),
+    (220, 32), (230, 64), (233, 96), (236, 128), (239, 160), (243, 192), (247, 224),
+    (251, 256), (300, 512), (330, 768), (360, 1024), (400, 1280), (420, 1024),
+    (450, 768), (500, 5120),
+];
+// TGM levels go up with every piece and every line, so they go a lot faster than ours:
+// ten lines take about 25 pieces, which makes about 35 TGM levels.
+const TGM_LEVELS_PER_LEVEL: u32 = 35;
+
+// How fast the pieces fall, depending on the level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GravityCurve {
+    // The curve of the guideline: (0.8 - (level - 1) * 0.007) ^ (level - 1) seconds per row.
+    // It reaches 20G at level 19.
+    #[default]
+    Guideline,
+    // The NES, where our level 1 is its level 0. It never goes faster than 1G.
+    NesNtsc,
+    // The arcade games, going all the way up to 20G.
+    Tgm,
+}
+
+impl GravityCurve {
+    // The gravity at the given level, in 1/65536 of a row per frame.
+    pub fn gravity(self, level: u32) -> u32 {
+        let index = level.max(1) - 1;
+        let gravity = match self {
+            GravityCurve::Guideline => {
+                // The formula stops making sense after a while (the base goes negative
+                // at level 116), but the curve is already at 20G long before that.
+                let index = index.min(19) as f64;
+                let seconds_per_row = (0.8 - index * 0.007).powf(index);
+                let rows_per_frame = 1.0 / (seconds_per_row * FRAMES_PER_SECOND as f64);
+                (rows_per_frame * ONE_G as f64).min(TWENTY_G as f64) as u32
+            }
+            GravityCurve::NesNtsc => {
+                let frames = NES_FRAMES_PER_ROW[(index as usize).min(NES_FRAMES_PER_ROW.len() - 1)];
+                ONE_G / frames
+            }
+            GravityCurve::Tgm => {
+                let tgm_level = index.saturating_mul(TGM_LEVELS_PER_LEVEL);
+                let &(_, gravity) = TGM_GRAVITY
+                    .iter()
+                    .rev()
+                    .find(|&&(from, _)| from <= tgm_level)
+                    .unwrap_or(&TGM_GRAVITY[0]);
+                gravity * (ONE_G / 256)
+            }
+        };
+        // Even the slowest level has to make the pieces fall.
+        gravity.clamp(1, TWENTY_G)
+    }
+}
+
+// What it takes to go to the next level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelGoal {
+    // A new level every given number of lines.
+    Fixed(u32),
+    // The guideline's variable goal: level `n` takes `5 * n` lines, but difficult clears
+    // count for more than their lines (see `Clear::awarded_lines`).
+    Variable,
+}
+
+impl Default for LevelGoal {
+    fn default() -> LevelGoal {
+        LevelGoal::Fixed(10)
+    }
+}
+
+// Keeps track of how close the player is to the next level.
+#[derive(Clone)]
+pub(crate) struct Levels {
+    goal: LevelGoal,
+    // The lines counted toward the current level.
+    progress: u32,
+}
+
+impl Levels {
+    pub(crate) fn new(goal: LevelGoal) -> Levels {
+        Levels { goal, progress: 0 }
+    }
+
+    // How many lines are still needed to leave the given level.
+    pub(crate) fn remaining(&self, level: u32) -> u32 {
+        self.goal_of(level).saturating_sub(self.progress)
+    }
+
+    // Counts what the piece which just locked did and returns the new level.
+    pub(crate) fn on_clear(&mut self, level: u32, clear: &Clear) -> u32 {
+        self.progress += match self.goal {
+            LevelGoal::Fixed(_) => clear.lines,
+            LevelGoal::Variable => clear.awarded_lines(),
+        };
+        let mut level = level;
+        while self.progress >= self.goal_of(level) {
+            self.progress -= self.goal_of(level);
+            level += 1;
+        }
+        level
+    }
+
+    fn goal_of(&self, level: u32) -> u32 {
+        match self.goal {
+            // A goal of zero lines would level up forever.
+            LevelGoal::Fixed(lines) => lines.max(1),
+            LevelGoal::Variable => 5 * level.max(1),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// This is the game engine. It holds everything that makes the game work
+// (the game map, the tetriminos, the scores...) but it doesn't know anything about SDL.
+// That way, the binary is just one frontend among others: bots, tests or any other
+// frontend can drive the very same engine through the `Game` type.
+extern crate rand;
+
+pub mod board;
+pub mod config;
+pub mod game;
+pub mod garbage;
+pub mod highscores;
+pub mod input;
+pub mod level;
+pub mod lock;
+pub mod mode;
+pub mod randomizer;
+pub mod rng;
+pub mod rotation;
+pub mod scoring;
+pub mod tetrimino;
+pub mod timer;
+pub mod visibility;
+
+pub use board::Board;
+pub use config::GameConfig;
+pub use game::{Game, GameEvent, Tetris};
+pub use garbage::Garbage;
+pub use input::{Handling, Inputs, Timing};
+pub use level::{GravityCurve, LevelGoal};
+pub use lock::LockMode;
+pub use mode::GameMode;
+pub use randomizer::{Randomizer, RandomizerKind};
+pub use rotation::RotationSystem;
+pub use scoring::{Clear, ClearStats, TSpin};
+pub use tetrimino::{PieceKind, Rotation, Tetrimino};
+pub use visibility::StackVisibility;
@@ -0,0 +1,167 @@
+// When a tetrimino touches the stack, the player gets a little time to slide or rotate it
+// before it locks. This is the lock delay, and every game has its own rules about it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockMode {
+    // No delay at all: the piece locks as soon as the gravity can't make it go down anymore.
+    Classic,
+    // The delay only starts over when the piece reaches a row lower than it has ever been.
+    StepReset,
+    // Moving or rotating the piece starts the delay over, but only `max_resets` times.
+    // Reaching a new lowest row gives the resets back. The guideline allows 15 of them.
+    MoveReset { max_resets: u32 },
+    // Moving or rotating the piece always starts the delay over.
+    Infinity,
+}
+
+impl Default for LockMode {
+    fn default() -> LockMode {
+        LockMode::MoveReset { max_resets: 15 }
+    }
+}
+
+// Keeps track of the lock delay of the current piece.
+pub(crate) struct LockDelay {
+    mode: LockMode,
+    // How many frames a piece can stay on the ground before locking.
+    delay: u32,
+    // How many frames the current piece has spent on the ground.
+    timer: u32,
+    resets: u32,
+    // The lowest row the current piece has been at. A piece only ever goes up
+    // because of a wall kick, so `y` getting bigger than this means real progress.
+    lowest_y: isize,
+}
+
+impl LockDelay {
+    pub(crate) fn new(mode: LockMode, delay: u32) -> LockDelay {
+        LockDelay { mode, delay, timer: 0, resets: 0, lowest_y: 0 }
+    }
+
+    // Every new piece gets a fresh delay.
+    pub(crate) fn new_piece(&mut self, y: isize) {
+        self.timer = 0;
+        self.resets = 0;
+        self.lowest_y = y;
+    }
+
+    // Whether the piece locks as soon as the gravity fails to move it down.
+    pub(crate) fn is_classic(&self) -> bool {
+        self.mode == LockMode::Classic
+    }
+
+    // Called whenever the player successfully moves or rotates the piece.
+    pub(crate) fn on_move(&mut self) {
+        match self.mode {
+            LockMode::MoveReset { max_resets } if self.resets < max_resets => {
+                self.timer = 0;
+                self.resets += 1;
+            }
+            LockMode::Infinity => self.timer = 0,
+            _ => {}
+        }
+    }
+
+    // Called whenever the piece went down, whatever the reason.
+    pub(crate) fn on_fall(&mut self, y: isize) {
+        if y > self.lowest_y {
+            self.lowest_y = y;
+            self.timer = 0;
+            self.resets = 0;
+        }
+    }
+
+    // Advances the delay by one frame and returns whether the piece has to lock now.
+    // The delay only runs while the piece is on the ground.
+    pub(crate) fn tick(&mut self, on_ground: bool) -> bool {
+        if self.is_classic() || !on_ground {
+            return false;
+        }
+        self.timer += 1;
+        let out_of_resets = match self.mode {
+            LockMode::MoveReset { max_resets } => self.resets >= max_resets,
+            _ => false,
+        };
+        self.timer >= self.delay || out_of_resets
+    }
+
+    // How close the piece is to locking, from 0 (just landed or in the air) to 1.
+    pub(crate) fn progress(&self) -> f32 {
+        if self.delay == 0 {
+            return 0.;
+        }
+        (self.timer as f32 / self.delay as f32).min(1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // How many frames on the ground it takes to lock.
+    fn frames_to_lock(lock_delay: &mut LockDelay) -> u32 {
+        (1..1000).find(|_| lock_delay.tick(true)).expect("the piece never locked")
+    }
+
+    #[test]
+    fn only_on_the_ground() {
+        let mut lock_delay = LockDelay::new(LockMode::default(), 30);
+        lock_delay.new_piece(0);
+        assert!((0..100).all(|_| !lock_delay.tick(false)));
+        assert_eq!(frames_to_lock(&mut lock_delay), 30);
+    }
+
+    #[test]
+    fn move_reset_runs_out() {
+        let mut lock_delay = LockDelay::new(LockMode::MoveReset { max_resets: 3 }, 30);
+        lock_delay.new_piece(0);
+        for _ in 0..3 {
+            assert!((0..20).all(|_| !lock_delay.tick(true)));
+            lock_delay.on_move();
+        }
+        // No reset left: the piece locks as soon as it's on the ground.
+        assert!(lock_delay.tick(true));
+        // Going lower than ever gives them back.
+        lock_delay.on_fall(1);
+        assert!(!lock_delay.tick(true));
+        lock_delay.on_move();
+        assert_eq!(frames_to_lock(&mut lock_delay), 30);
+    }
+
+    #[test]
+    fn kicks_up_dont_give_resets_back() {
+        let mut lock_delay = LockDelay::new(LockMode::MoveReset { max_resets: 1 }, 30);
+        lock_delay.new_piece(5);
+        lock_delay.on_move();
+        lock_delay.on_fall(4);
+        lock_delay.on_fall(5);
+        assert!(lock_delay.tick(true));
+    }
+
+    #[test]
+    fn step_reset_ignores_moves() {
+        let mut lock_delay = LockDelay::new(LockMode::StepReset, 30);
+        lock_delay.new_piece(0);
+        assert!((0..20).all(|_| !lock_delay.tick(true)));
+        lock_delay.on_move();
+        assert_eq!(frames_to_lock(&mut lock_delay), 10);
+    }
+
+    #[test]
+    fn infinity_never_runs_out() {
+        let mut lock_delay = LockDelay::new(LockMode::Infinity, 30);
+        lock_delay.new_piece(0);
+        for _ in 0..100 {
+            assert!((0..20).all(|_| !lock_delay.tick(true)));
+            lock_delay.on_move();
+        }
+        assert_eq!(frames_to_lock(&mut lock_delay), 30);
+    }
+
+    #[test]
+    fn classic_leaves_it_to_the_gravity() {
+        let mut lock_delay = LockDelay::new(LockMode::Classic, 30);
+        lock_delay.new_piece(0);
+        assert!(lock_delay.is_classic());
+        assert!((0..100).all(|_| !lock_delay.tick(true)));
+    }
+}
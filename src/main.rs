@@ -1,247 +1,402 @@
 extern crate sdl2;
+extern crate tetris;
 
-use sdl2::pixels::Color;
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::keyboard::{Keycode, Scancode};
 use std::thread::sleep;
-use std::time::{Duration, SystemTime};
-use sdl2::render::{TextureCreator, Texture, Canvas};
-use sdl2::rect::Rect;
-use sdl2::video::{Window, WindowContext};
-use sdl2::image::{INIT_PNG, INIT_JPG, LoadTexture};
-use std::io;
-use std::fs::File;
-use std::io::{Write, Read};
-use sdl2::version::version;
+use std::time::Duration;
+use sdl2::render::TextureCreator;
+use sdl2::image::{INIT_PNG, INIT_JPG};
 
-mod tetrimino;
+use std::env;
+use std::sync::Arc;
 
-use tetrimino::*;
+use tetris::{Game, GameConfig, GameEvent, GameMode, Garbage, GravityCurve, Inputs, LevelGoal,
+             LockMode, PieceKind, RandomizerKind, StackVisibility};
+use tetris::highscores::{insert_highscore, insert_sprint_record, load_highscores, load_marathon_highscores,
+                         load_sprint_records, load_ultra_highscores, save_highscores,
+                         save_marathon_highscores, save_sprint_records, save_ultra_highscores,
+                         HighScore, SprintRecord};
+use tetris::board::MAX_WIDTH;
+use tetris::input::FRAMES_PER_SECOND;
+use tetris::level::MAX_START_LEVEL;
+use tetris::rotation::{Ars, Legacy, Nes, Srs};
+use tetris::timer::format_time;
 
-const TEXTURE_SIZE: u32 = 32;
+mod font;
+mod renderer;
 
-enum TextureColor {
-    BlueR60G136B207,
-    GreenR73G196B137,
-}
+use renderer::Renderer;
 
-// Tetris will hold all the game's information:
-// - Game map
-// - Current level
-// - Score
-// - Number of lines
-// - The current tetrimino
-// - Some potential other information ( such as a ghost, or the preview of the next tetrimino )
-struct Tetris {
-    game_map: Vec<Vec<u8>>,
-    current_level: u32,
-    score: u32,
-    nb_lines: u32,
-    current_piece: Option<Tetrimino>,
-}
+fn main() {
+    let sdl_context = sdl2::init().expect("SDL initialization failed");
+    let video_subsystem = sdl_context
+        .video()
+        .expect("could not get video subsystem");
 
-impl Tetris {
-    fn new() -> Tetris {
-        let mut game_map = Vec::new();
-        // We know that a `tetris` map has a width of 10 blocks and a height of 16 blocks.
-        // This loop create our game map by looping over the number of lines
-        // and generating an empty vector of 10 blocks, which will be a line.
-        for _ in 0..16 {
-            game_map.push(vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
-        }
-        // Apart of game map, everything else is very straightforward
-        Tetris {
-            game_map,
-            current_level: 1,
-            score: 0,
-            nb_lines: 0,
-            current_piece: None,
-        }
-    }
+    sdl2::image::init(INIT_PNG | INIT_JPG)
+        .expect("could not initialize image context");
 
-    fn main() {
-        let sdl_context = sdl2::init().expect("SDL initialization failed");
-        let video_subsystem = sdl_context
-            .video()
-            .expect("could not get video subsystem");
-
-        sdl2::image::init(INIT_PNG | INIT_JPG)
-            .expect("could not initialize image context");
-
-        let window = video_subsystem
-            .window("rust-sdl2 demo video", 800, 600)
-            // position_centered gets the window in the middle of the screen
-            .position_centered()
-            // opengl makes the SDL use opengl to render
-            .opengl()
-            // build creates a window by applying all previously received parameters
-            .build()
-            // expect panics with the given message if an error occurred
-            .expect("failed to create window");
-
-        let mut canvas = window
-            // into_canvas transforms the window into a canvas so that we can manipulate it more easily
-            .into_canvas()
-            // target_texture actives texture rendering support
-            .target_texture()
-            // present_vsync enables the v-sync ( also known as vertical synchronization ) limit
-            .present_vsync()
-            // build creates the canvas by applying all previously set parameters
-            .build()
-            .expect("failed to convert window to canvas");
-
-        let texture_creator: TextureCreator<_> = canvas.texture_creator();
-        let blue_square_texture: Texture = create_square_texture_rect(
-            &mut canvas,
-            &texture_creator,
-            TextureColor::BlueR60G136B207,
-            TEXTURE_SIZE).expect("failed to create blue square texture");
-        let green_square_texture: Texture = create_square_texture_rect(
-            &mut canvas,
-            &texture_creator,
-            TextureColor::GreenR73G196B137,
-            TEXTURE_SIZE).expect("failed to create green square texture");
-
-        let image_texture = texture_creator
-            .load_texture("assets/rust_does_not_compile.png").expect("could not load image");
-
-        let timer = SystemTime::now();
-
-        let mut event_pump = sdl_context.
-            event_pump()
-            .expect("failed to get sdl event pump");
-
-        // We added a label `running` to the main loop.
-        // The point is to able to break directly an upper loop without having to set a variable
-        'running: loop {
-            for event in event_pump.poll_iter() {
-                match event {
-                    Event::Quit { .. } |
-                    Event::KeyDown { keycode: Some(Keycode::Escape), .. } =>
-                        { break 'running; }
-                    _ => {}
-                }
-            }
-            canvas.set_draw_color(Color::RGB(156, 40, 8));
-            canvas.clear();
-
-            canvas.copy(&image_texture, None, None).expect("render failed");
-
-            // let display_green = match timer.elapsed() {
-            //     Ok(elapsed) => elapsed.as_secs() % 2 == 0,
-            //     Err(_) => {
-            //         // in case of error, currently, we do nothing
-            //         true
-            //     }
-            // };
-            //
-            // let square_texture = if display_green {
-            //     &green_square_texture
-            // } else {
-            //     &blue_square_texture
-            // };
-            //
-            // canvas
-            //     .copy(
-            //         &square_texture,
-            //         None,
-            //         Rect::new(0, 0, TEXTURE_SIZE, TEXTURE_SIZE),
-            //     )
-            //     .expect("could not copy texture into window");
-            canvas.present();
-
-            // we sleep enough to get ~60fps. If we don't call this, the program will take
-            // 100% of a CPU time
-            sleep(Duration::new(0, 1_000_000_000u32 / 60))
-        }
-    }
+    let window = video_subsystem
+        .window("rust-sdl2 demo video", 800, 600)
+        // position_centered gets the window in the middle of the screen
+        .position_centered()
+        // opengl makes the SDL use opengl to render
+        .opengl()
+        // build creates a window by applying all previously received parameters
+        .build()
+        // expect panics with the given message if an error occurred
+        .expect("failed to create window");
 
-    fn create_square_texture_rect<'a>(
-        canvas: &mut Canvas<Window>,
-        texture_creator: &'a TextureCreator<WindowContext>,
-        color: TextureColor,
-        size: u32,
-    ) -> Option<Texture<'a>> {
-        if let Ok(mut square_texture) = texture_creator
-            .create_texture_target(None, size, size) {
-            canvas.with_texture_canvas(&mut square_texture, |texture| {
-                match color {
-                    TextureColor::BlueR60G136B207 =>
-                        texture.set_draw_color(Color::RGB(60, 136, 207)),
-                    TextureColor::GreenR73G196B137 =>
-                        texture.set_draw_color(Color::RGB(73, 196, 137))
-                }
-                texture.clear()
-            }).expect("failed to color a texture");
-            Some(square_texture)
-        } else { None }
-    }
+    let mut canvas = window
+        // into_canvas transforms the window into a canvas so that we can manipulate it more easily
+        .into_canvas()
+        // target_texture actives texture rendering support
+        .target_texture()
+        // present_vsync enables the v-sync ( also known as vertical synchronization ) limit
+        .present_vsync()
+        // build creates the canvas by applying all previously set parameters
+        .build()
+        .expect("failed to convert window to canvas");
+
+    let texture_creator: TextureCreator<_> = canvas.texture_creator();
+    let mut renderer = Renderer::new(&mut canvas, &texture_creator);
 
-    fn slice_to_string(slice: &[u32]) -> String {
-        slice
-            // Here we create an iterator from our slice
-            // A really important and fundamental thing to note about iterators is in Rust;
-            // they're lazy.
-            // Create an iterator doesn't cost anything more than the size of type.
-            // (generally a structure containing a pointer and an index).
-            // Until the next() method is called, nothing happens.
-            .iter()
-            // We call the iterator's map method.
-            // What it does is simple: it converts the current type into another one.
-            // Really important to note: at this point, the iterator still hasn't done anything/
-            // Keep in mid that nothing is done util the next() method is called.
-            .map(|highscore| highscore.to_string())
-            // And now we call the collect() method.
-            // It'll call the next() method of our iterator as long as
-            // it didn't get all elements and store them into a Vec.
-            // This is where map() method will be called on every element of out iterator
-            .collect::<Vec<String>>()
-            // And finally the last step: This method (as its name indicates) joins all the elements
-            // of the Vec into a String separated by the given &str (so, " " in our case).
-            .join(" ")
+    let config = config_from_args();
+    let requested_mode = config.mode;
+    let mut game = Game::with_config(config);
+    // The game leaves some room above the garbage: the player should know if it took some away.
+    if let (GameMode::Dig { rows, .. }, GameMode::Dig { rows: used_rows, .. }) = (requested_mode, game.config().mode) {
+        if rows != used_rows {
+            println!("{} garbage lines don't leave enough room in the game map, using {}", rows, used_rows);
+        }
     }
 
-    fn save_highscores_and_lines(highscores: &[u32], number_of_lines: &[u32]) -> bool {
-        let s_highscores = slice_to_string(highscores);
-        let s_number_of_lines = slice_to_string(number_of_lines);
+    let mut event_pump = sdl_context.
+        event_pump()
+        .expect("failed to get sdl event pump");
 
-        // The is_ok() method call just informs the caller of the save_highscores_and_lines() function
-        // if everything has been saved as expected or not.
-        write_into_file(format!("{}\n{}\n", s_highscores,
-                                s_number_of_lines).as_ref(), "scores.txt").is_ok()
-    }
+    // We added a label `running` to the main loop.
+    // The point is to able to break directly an upper loop without having to set a variable
+    'running: loop {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } =>
+                    { break 'running; }
+                // The zen mode tools: they don't go through `step` since they aren't
+                // part of playing, and the game ignores them in the other modes.
+                Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => { game.undo(); }
+                Event::KeyDown { keycode: Some(Keycode::Return), .. } => { game.redo(); }
+                Event::KeyDown { keycode: Some(Keycode::G), .. } => { game.toggle_gravity(); }
+                // The number keys pick the next piece, in the order of `PieceKind::ALL`.
+                Event::KeyDown { keycode: Some(keycode), .. } => {
+                    let number = keycode as i32 - Keycode::Num1 as i32;
+                    if number >= 0 && (number as usize) < PieceKind::ALL.len() {
+                        game.choose_next_piece(PieceKind::ALL[number as usize]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        renderer.draw(&mut canvas, &game);
+        canvas.present();
 
-    fn write_into_file(content: &str, filename: &str) -> io::Result<()> {
-        // try! marco can be replaced with ? operator
-        let mut f = File::create(filename)?;
-        f.write_all(content.as_bytes())
-    }
+        // The engine doesn't know about SDL, so we translate the keyboard state
+        // into the buttons it understands and let it advance by one frame.
+        let keyboard = event_pump.keyboard_state();
+        let events = game.step(Inputs {
+            left: keyboard.is_scancode_pressed(Scancode::Left),
+            right: keyboard.is_scancode_pressed(Scancode::Right),
+            soft_drop: keyboard.is_scancode_pressed(Scancode::Down),
+            hard_drop: keyboard.is_scancode_pressed(Scancode::Space),
+            sonic_drop: keyboard.is_scancode_pressed(Scancode::V),
+            rotate_cw: keyboard.is_scancode_pressed(Scancode::Up)
+                || keyboard.is_scancode_pressed(Scancode::X),
+            rotate_ccw: keyboard.is_scancode_pressed(Scancode::Z)
+                || keyboard.is_scancode_pressed(Scancode::LCtrl),
+            hold: keyboard.is_scancode_pressed(Scancode::C)
+                || keyboard.is_scancode_pressed(Scancode::LShift),
+        });
+        renderer.update(&events, &game);
+        // Once the game is over, the summary stays on the screen until the player leaves.
+        if events.contains(&GameEvent::GameOver) || events.contains(&GameEvent::Completed) {
+            save_result(&game);
+        }
 
-    fn line_to_slice(line: &str) -> Vec<u32> {
-        line.split(" ").filter_map(|nb| nb.parse::<u32>().ok()).collect()
+        // we sleep enough to get ~60fps. If we don't call this, the program will take
+        // 100% of a CPU time
+        sleep(Duration::new(0, 1_000_000_000u32 / FRAMES_PER_SECOND))
     }
+}
 
-    fn load_highscores_and_lines() -> Option<(Vec<u32>, Vec<u32>)> {
-        if let Ok(content) = read_from_file("scores.txt") {
-            let mut lines = content.splitn(2, "\n").map(|line|
-                line_to_slice(line)).collect::<Vec<_>>();
-            if lines.len() == 2 {
-                let (number_lines, highscores) = (lines.pop().unwrap(),
-                                                  lines.pop().unwrap());
-                Some((highscores, number_lines))
+// Prints how the game went and saves it in the table of its mode, if it's good enough.
+fn save_result(game: &Game) {
+    let tetris = game.tetris();
+    let highscore = HighScore { score: tetris.score, lines: tetris.nb_lines, seed: game.seed() };
+    match game.config().mode {
+        GameMode::Endless => {
+            println!("Game over! Score: {}, lines: {}, time: {}, seed: {}",
+                     tetris.score, tetris.nb_lines, format_time(game.time()), game.seed());
+            let mut highscores = load_highscores();
+            if insert_highscore(&mut highscores, highscore) && !save_highscores(&highscores) {
+                println!("failed to save the high scores");
+            }
+        }
+        // Every line goal has its own table, like the sprint.
+        GameMode::Marathon { lines } => {
+            println!("{} Score: {}, lines: {}, time: {}, seed: {}",
+                     if game.is_completed() { "Marathon complete!" } else { "Game over!" },
+                     tetris.score, tetris.nb_lines, format_time(game.time()), game.seed());
+            let mut highscores = load_marathon_highscores(lines);
+            if insert_highscore(&mut highscores, highscore) && !save_marathon_highscores(lines, &highscores) {
+                println!("failed to save the marathon high scores");
+            }
+        }
+        GameMode::Ultra { duration } => {
+            println!("{} Score: {}, lines: {}, seed: {}",
+                     if game.is_completed() { "Time's up!" } else { "Game over!" },
+                     tetris.score, tetris.nb_lines, game.seed());
+            // Topping out before the end still counts: the score is what it is.
+            let seconds = duration.as_secs();
+            let mut highscores = load_ultra_highscores(seconds);
+            if insert_highscore(&mut highscores, highscore) && !save_ultra_highscores(seconds, &highscores) {
+                println!("failed to save the ultra high scores");
+            }
+        }
+        GameMode::Dig { rise: None, .. } => {
+            if game.is_completed() {
+                println!("All the garbage is gone in {}, seed: {}", format_time(game.time()), game.seed());
             } else {
-                None
+                println!("Game over! {} garbage lines left, seed: {}", game.garbage_left(), game.seed());
+            }
+        }
+        GameMode::Dig { rise: Some(_), .. } => {
+            println!("Game over! {} garbage lines cleared in {}, seed: {}",
+                     game.garbage_cleared(), format_time(game.time()), game.seed());
+        }
+        // Nothing to save: the zen mode never ends anyway.
+        GameMode::Zen => {}
+        GameMode::Sprint { lines } => {
+            // Topping out in a sprint is just a loss.
+            if !game.is_completed() {
+                println!("Game over! {} lines out of {}, seed: {}", tetris.nb_lines, lines, game.seed());
+                return;
+            }
+            println!("{} lines in {}, seed: {}", lines, format_time(game.time()), game.seed());
+            for (index, split) in game.splits().iter().enumerate() {
+                println!("  {} lines: {}", (index + 1) * 10, format_time(*split));
+            }
+            let mut records = load_sprint_records(lines);
+            let record = SprintRecord { time: game.time(), seed: game.seed() };
+            if insert_sprint_record(&mut records, record) && !save_sprint_records(lines, &records) {
+                println!("failed to save the sprint records");
             }
-        } else {
-            None
         }
     }
+}
 
-    // This time, it only tales a filename as an argument
-// and returns a String if the reading was successful.
-    fn read_from_file(filename: &str) -> io::Result<String> {
-        let mut f = File::open(filename)?;
-        let mut content = String::new();
-        f.read_to_string(&mut content)?;
-        Ok(content)
+// Reads the game rules from the command line arguments, such as `--rotation=ars`.
+// Anything we don't know about is ignored and the defaults are used instead.
+fn config_from_args() -> GameConfig {
+    let mut config = GameConfig::default();
+    // The garbage options only make sense for the dig mode,
+    // which could come after them: we keep them for the end.
+    let mut garbage = Garbage::default();
+    let mut rise = None;
+    for arg in env::args().skip(1) {
+        let mut parts = arg.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            // `endless`, `marathon` or `sprint` with an optional line goal (`marathon:200`,
+            // `marathon:endless`, `sprint:20`), `ultra` with an optional duration
+            // in seconds (`ultra:180`), `dig` with an optional number of garbage lines (`dig:18`)
+            // or `zen`.
+            (Some("--mode"), Some(mode)) => {
+                config.mode = match mode {
+                    "endless" => GameMode::Endless,
+                    "sprint" => GameMode::sprint(),
+                    _ if mode.starts_with("sprint:") => match mode["sprint:".len()..].parse() {
+                        Ok(lines) if lines > 0 => GameMode::Sprint { lines },
+                        _ => {
+                            println!("invalid sprint goal `{}`, using 40 lines", mode);
+                            GameMode::sprint()
+                        }
+                    },
+                    "marathon" => GameMode::marathon(),
+                    _ if mode.starts_with("marathon:") => match mode["marathon:".len()..].parse() {
+                        Ok(lines) if lines > 0 => GameMode::Marathon { lines },
+                        // A marathon without an end is just the endless mode.
+                        _ if &mode["marathon:".len()..] == "endless" => GameMode::Endless,
+                        _ => {
+                            println!("invalid marathon goal `{}`, using 150 lines", mode);
+                            GameMode::marathon()
+                        }
+                    },
+                    "dig" => GameMode::dig(),
+                    _ if mode.starts_with("dig:") => match mode["dig:".len()..].parse() {
+                        Ok(rows) if rows > 0 => GameMode::Dig { rows, garbage: Garbage::default(), rise: None },
+                        _ => {
+                            println!("invalid number of garbage lines `{}`, using 10", mode);
+                            GameMode::dig()
+                        }
+                    },
+                    "zen" => GameMode::Zen,
+                    "ultra" => GameMode::ultra(),
+                    _ if mode.starts_with("ultra:") => match mode["ultra:".len()..].parse() {
+                        Ok(seconds) if seconds > 0 => GameMode::Ultra { duration: Duration::from_secs(seconds) },
+                        _ => {
+                            println!("invalid ultra duration `{}`, using 2 minutes", mode);
+                            GameMode::ultra()
+                        }
+                    },
+                    _ => {
+                        println!("unknown mode `{}`, using the endless one", mode);
+                        GameMode::Endless
+                    }
+                }
+            }
+            (Some("--garbage"), Some(name)) => {
+                garbage = match name {
+                    "messy" => Garbage::Messy,
+                    "clean" => Garbage::Clean,
+                    _ => {
+                        println!("unknown garbage `{}`, using messy garbage", name);
+                        Garbage::Messy
+                    }
+                }
+            }
+            // How often a garbage line comes up in the dig mode, which then never ends.
+            (Some("--rise"), Some(value)) => match value.parse() {
+                Ok(value) => rise = Some(value),
+                Err(_) => println!("invalid timing `{}`, use frames (`300`) or milliseconds (`5000ms`)",
+                                   value),
+            },
+            (Some("--rotation"), Some(name)) => {
+                config.rotation_system = match name {
+                    "srs" => Arc::new(Srs),
+                    "ars" => Arc::new(Ars),
+                    "nes" => Arc::new(Nes),
+                    "legacy" => Arc::new(Legacy),
+                    _ => {
+                        println!("unknown rotation system `{}`, using SRS", name);
+                        Arc::new(Srs)
+                    }
+                }
+            }
+            (Some("--randomizer"), Some(name)) => {
+                config.randomizer = match name {
+                    "7bag" => RandomizerKind::SevenBag,
+                    "14bag" => RandomizerKind::FourteenBag,
+                    "history" => RandomizerKind::History,
+                    "nes" => RandomizerKind::Nes,
+                    "random" => RandomizerKind::Uniform,
+                    // A fixed sequence is given with the letters of the pieces: `sequence:IJLOSZT`.
+                    _ if name.starts_with("sequence:") => RandomizerKind::Sequence(
+                        name["sequence:".len()..].chars().filter_map(PieceKind::from_letter).collect()),
+                    _ => {
+                        println!("unknown randomizer `{}`, using the 7-bag", name);
+                        RandomizerKind::SevenBag
+                    }
+                }
+            }
+            (Some(size @ "--width"), Some(value))
+            | (Some(size @ "--height"), Some(value))
+            | (Some(size @ "--hidden"), Some(value)) => match value.parse() {
+                Ok(value) => match size {
+                    "--width" if value > MAX_WIDTH => {
+                        println!("the game map can't be more than {} blocks wide, not {}", MAX_WIDTH, value);
+                        config.width = MAX_WIDTH;
+                    }
+                    "--width" => config.width = value,
+                    "--height" => config.height = value,
+                    _ => config.hidden_rows = value,
+                },
+                Err(_) => println!("invalid board size `{}`", value),
+            },
+            (Some("--preview"), Some(count)) => match count.parse::<usize>() {
+                Ok(count) if count <= 6 => config.preview_count = count,
+                _ => println!("the preview shows between 0 and 6 pieces, not `{}`", count),
+            },
+            (Some("--ghost"), Some(ghost)) => config.ghost = ghost != "off",
+            (Some("--big"), Some(big)) => config.big = big != "off",
+            // `visible`, `invisible`, or `fade:` with how long the blocks stay (`fade:300`, `fade:5000ms`).
+            (Some("--stack"), Some(stack)) => {
+                config.stack_visibility = match stack {
+                    "visible" => StackVisibility::Visible,
+                    "invisible" => StackVisibility::Invisible,
+                    _ if stack.starts_with("fade:") => match stack["fade:".len()..].parse() {
+                        Ok(timing) => StackVisibility::Fading(timing),
+                        Err(_) => {
+                            println!("invalid fading time `{}`, the blocks disappear right away", stack);
+                            StackVisibility::Invisible
+                        }
+                    },
+                    _ => {
+                        println!("unknown stack visibility `{}`, showing the stack", stack);
+                        StackVisibility::Visible
+                    }
+                }
+            }
+            (Some("--lock"), Some(mode)) => {
+                config.lock_mode = match mode {
+                    "classic" => LockMode::Classic,
+                    "step" => LockMode::StepReset,
+                    "infinity" => LockMode::Infinity,
+                    "move" => LockMode::default(),
+                    _ => {
+                        println!("unknown lock mode `{}`, using the move reset", mode);
+                        LockMode::default()
+                    }
+                }
+            }
+            (Some("--lock-delay"), Some(frames)) => match frames.parse() {
+                Ok(frames) => config.lock_delay = frames,
+                Err(_) => println!("invalid lock delay `{}`", frames),
+            },
+            (Some(timing @ "--das"), Some(value))
+            | (Some(timing @ "--arr"), Some(value))
+            | (Some(timing @ "--das-cut"), Some(value)) => match value.parse() {
+                Ok(value) => match timing {
+                    "--das" => config.handling.das = value,
+                    "--arr" => config.handling.arr = value,
+                    _ => config.handling.das_cut = value,
+                },
+                Err(_) => println!("invalid timing `{}`, use frames (`10`) or milliseconds (`160ms`)",
+                                   value),
+            },
+            (Some("--level"), Some(level)) => match level.parse::<u32>() {
+                Ok(level) if level > 0 && level <= MAX_START_LEVEL => config.start_level = level,
+                _ => println!("invalid level `{}`, levels go from 1 to {}", level, MAX_START_LEVEL),
+            },
+            // Either `variable` or the number of lines each level takes.
+            (Some("--goal"), Some(goal)) => match goal {
+                "variable" => config.level_goal = LevelGoal::Variable,
+                _ => match goal.parse::<u32>() {
+                    Ok(lines) if lines > 0 => config.level_goal = LevelGoal::Fixed(lines),
+                    _ => println!("invalid goal `{}`, use `variable` or a number of lines", goal),
+                },
+            },
+            (Some("--gravity"), Some(curve)) => {
+                config.gravity = match curve {
+                    "guideline" => GravityCurve::Guideline,
+                    "nes" => GravityCurve::NesNtsc,
+                    "tgm" => GravityCurve::Tgm,
+                    _ => {
+                        println!("unknown gravity curve `{}`, using the guideline one", curve);
+                        GravityCurve::Guideline
+                    }
+                }
+            }
+            (Some("--seed"), Some(seed)) => match seed.parse() {
+                Ok(seed) => config.seed = seed,
+                Err(_) => println!("invalid seed `{}`, using a random one", seed),
+            },
+            _ => println!("ignoring unknown argument `{}`", arg),
+        }
+    }
+    if let GameMode::Dig { garbage: ref mut dig_garbage, rise: ref mut dig_rise, .. } = config.mode {
+        *dig_garbage = garbage;
+        *dig_rise = rise;
     }
+    config
+}
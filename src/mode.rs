@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use crate::garbage::Garbage;
+use crate::input::Timing;
+
+// The different ways to play. They all share the same rules, only the goal changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GameMode {
+    // Play until the stack reaches the top. This is the classic game, the endless marathon.
+    #[default]
+    Endless,
+    // Go through the levels until the given number of lines: 150 in the guideline,
+    // 200 on the NES.
+    Marathon { lines: u32 },
+    // Clear the given number of lines (usually 20, 40 or 100) as fast as possible.
+    Sprint { lines: u32 },
+    // Score as many points as possible before the time runs out.
+    Ultra { duration: Duration },
+    // The game map starts with `rows` lines of garbage, each with one hole: clear them all
+    // as fast as possible. With `rise`, a new garbage line comes up from the bottom
+    // every time it elapses and the game only ends when the stack reaches the top.
+    Dig { rows: u32, garbage: Garbage, rise: Option<Timing> },
+    // Practice without any pressure: the game never ends, the player can undo and redo
+    // their placements, pick the next piece and turn the gravity off (see `Game::undo`).
+    Zen,
+}
+
+impl GameMode {
+    // The guideline marathon, 150 lines.
+    pub fn marathon() -> GameMode {
+        GameMode::Marathon { lines: 150 }
+    }
+
+    // The usual sprint, 40 lines.
+    pub fn sprint() -> GameMode {
+        GameMode::Sprint { lines: 40 }
+    }
+
+    // The usual ultra, 2 minutes.
+    pub fn ultra() -> GameMode {
+        GameMode::Ultra { duration: Duration::from_secs(120) }
+    }
+
+    // The usual dig race, 10 messy lines.
+    pub fn dig() -> GameMode {
+        GameMode::Dig { rows: 10, garbage: Garbage::Messy, rise: None }
+    }
+
+    // The number of lines which ends the game, if there is one.
+    pub fn line_goal(self) -> Option<u32> {
+        match self {
+            GameMode::Marathon { lines } | GameMode::Sprint { lines } => Some(lines.max(1)),
+            _ => None,
+        }
+    }
+
+    // How long the game lasts, if it has a time limit.
+    pub fn time_limit(self) -> Option<Duration> {
+        match self {
+            GameMode::Ultra { duration } => Some(duration),
+            _ => None,
+        }
+    }
+}
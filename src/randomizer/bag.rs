@@ -0,0 +1,33 @@
+use rand::Rng;
+
+use crate::randomizer::Randomizer;
+use crate::rng::GameRng;
+use crate::tetrimino::PieceKind;
+
+// The bag randomizer puts every tetrimino in a bag (`copies` times each), shuffles it
+// and deals the pieces one by one. Once the bag is empty, a new one is made.
+// With one copy of each, this is the "7-bag" of the guideline: you never wait more than
+// 12 pieces for an I and you never get more than two of the same piece in a row.
+#[derive(Clone)]
+pub struct Bag {
+    copies: usize,
+    bag: Vec<PieceKind>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Bag {
+        Bag { copies, bag: Vec::new() }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next_piece(&mut self, rng: &mut GameRng) -> PieceKind {
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend_from_slice(&PieceKind::ALL);
+            }
+            rng.shuffle(&mut self.bag);
+        }
+        self.bag.pop().expect("a new bag is never empty")
+    }
+}
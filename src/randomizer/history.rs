@@ -0,0 +1,57 @@
+use rand::Rng;
+
+use crate::randomizer::Randomizer;
+use crate::rng::GameRng;
+use crate::tetrimino::PieceKind;
+
+// How many times the randomizer rolls again, after the first draw, to find a piece
+// which isn't in the history.
+const REROLLS: usize = 6;
+
+// The randomizer of the TGM series: it remembers the last 4 pieces and rolls again
+// (up to 6 times) when it draws one of them. If all the rolls failed, the last one is kept.
+//
+// The history starts full of S and Z, and the first piece is never an S, a Z or an O,
+// so the game never begins with a piece which can't be placed without making a hole.
+#[derive(Clone)]
+pub struct History {
+    history: [PieceKind; 4],
+    first: bool,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            history: [PieceKind::Z, PieceKind::S, PieceKind::Z, PieceKind::S],
+            first: true,
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new()
+    }
+}
+
+impl Randomizer for History {
+    fn next_piece(&mut self, rng: &mut GameRng) -> PieceKind {
+        let piece = if self.first {
+            self.first = false;
+            *rng.choose(&[PieceKind::I, PieceKind::J, PieceKind::L, PieceKind::T])
+                .expect("there is always a first piece to pick")
+        } else {
+            let mut piece = *rng.choose(&PieceKind::ALL).expect("there are 7 pieces to pick");
+            for _ in 0..REROLLS {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = *rng.choose(&PieceKind::ALL).expect("there are 7 pieces to pick");
+            }
+            piece
+        };
+        self.history.rotate_left(1);
+        self.history[3] = piece;
+        piece
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use crate::rng::GameRng;
+use crate::tetrimino::PieceKind;
+
+mod bag;
+mod history;
+mod nes;
+mod sequence;
+mod uniform;
+
+pub use self::bag::Bag;
+pub use self::history::History;
+pub use self::nes::Nes;
+pub use self::sequence::Sequence;
+pub use self::uniform::Uniform;
+
+// How many upcoming pieces the queue always knows about in advance.
+pub const QUEUE_LENGTH: usize = 6;
+
+// A randomizer decides which tetrimino comes next.
+// Calling `rand::random` for every piece is a bit too random: you can get the same piece
+// many times in a row or wait forever for an I. Randomizers exist to fix that.
+//
+// They don't own any source of randomness: the game gives them its own,
+// so a game never depends on some global state.
+//
+// A randomizer also has to be `Clone`, so a game can go back to an earlier state
+// (see the undo of the zen mode) and get the same pieces again.
+pub trait Randomizer: Send + RandomizerClone {
+    fn next_piece(&mut self, rng: &mut GameRng) -> PieceKind;
+}
+
+// `Clone` can't be used on a `Box<dyn Randomizer>` directly: this does it for any randomizer
+// which is `Clone`, so nobody has to write it by hand.
+pub trait RandomizerClone {
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+
+impl<T: Randomizer + Clone + 'static> RandomizerClone for T {
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Box<dyn Randomizer> {
+        self.clone_box()
+    }
+}
+
+// The randomizers a game can be configured with.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RandomizerKind {
+    // Every group of 7 pieces contains each tetrimino once.
+    #[default]
+    SevenBag,
+    // Every group of 14 pieces contains each tetrimino twice.
+    FourteenBag,
+    // TGM: avoids the last 4 pieces, with up to 6 tries.
+    History,
+    // NES: rolls again once when the same piece comes twice in a row.
+    Nes,
+    // Every piece has the same chance, every time.
+    Uniform,
+    // The given pieces, in order, over and over again.
+    Sequence(Vec<PieceKind>),
+}
+
+impl RandomizerKind {
+    pub fn build(&self) -> Box<dyn Randomizer> {
+        match *self {
+            RandomizerKind::SevenBag => Box::new(Bag::new(1)),
+            RandomizerKind::FourteenBag => Box::new(Bag::new(2)),
+            RandomizerKind::History => Box::new(History::new()),
+            RandomizerKind::Nes => Box::new(Nes::new()),
+            RandomizerKind::Uniform => Box::new(Uniform),
+            RandomizerKind::Sequence(ref pieces) => Box::new(Sequence::new(pieces.clone())),
+        }
+    }
+}
+
+// The upcoming pieces, in the order they'll come.
+// The queue always holds `QUEUE_LENGTH` pieces, so the next ones can be looked at
+// without having to ask the randomizer anything.
+#[derive(Clone)]
+pub struct PieceQueue {
+    randomizer: Box<dyn Randomizer>,
+    upcoming: VecDeque<PieceKind>,
+}
+
+impl PieceQueue {
+    pub fn new(mut randomizer: Box<dyn Randomizer>, rng: &mut GameRng) -> PieceQueue {
+        let upcoming = (0..QUEUE_LENGTH).map(|_| randomizer.next_piece(rng)).collect();
+        PieceQueue { randomizer, upcoming }
+    }
+
+    // Takes the first piece of the queue and asks the randomizer for a new one to replace it.
+    pub fn pop(&mut self, rng: &mut GameRng) -> PieceKind {
+        self.upcoming.push_back(self.randomizer.next_piece(rng));
+        self.upcoming.pop_front().expect("the piece queue is never empty")
+    }
+
+    // Makes the given piece the next one, in place of the one the randomizer picked.
+    pub fn replace_next(&mut self, kind: PieceKind) {
+        self.upcoming[0] = kind;
+    }
+
+    // The upcoming pieces, the next one first.
+    pub fn peek(&self) -> impl Iterator<Item = PieceKind> + '_ {
+        self.upcoming.iter().cloned()
+    }
+}
@@ -0,0 +1,37 @@
+use rand::Rng;
+
+use crate::randomizer::Randomizer;
+use crate::rng::GameRng;
+use crate::tetrimino::PieceKind;
+
+// The randomizer of the NES version. It rolls a number between 0 and 7: if it's 7
+// or the same piece as the previous one, it rolls again between 0 and 6 and keeps
+// whatever comes out. Repeats are rarer than with a plain random, but they still happen.
+#[derive(Clone)]
+pub struct Nes {
+    previous: Option<PieceKind>,
+}
+
+impl Nes {
+    pub fn new() -> Nes {
+        Nes { previous: None }
+    }
+}
+
+impl Default for Nes {
+    fn default() -> Nes {
+        Nes::new()
+    }
+}
+
+impl Randomizer for Nes {
+    fn next_piece(&mut self, rng: &mut GameRng) -> PieceKind {
+        let roll = rng.gen_range(0, PieceKind::ALL.len() + 1);
+        let piece = match PieceKind::ALL.get(roll) {
+            Some(&piece) if Some(piece) != self.previous => piece,
+            _ => PieceKind::ALL[rng.gen_range(0, PieceKind::ALL.len())],
+        };
+        self.previous = Some(piece);
+        piece
+    }
+}
@@ -0,0 +1,28 @@
+use crate::randomizer::Randomizer;
+use crate::rng::GameRng;
+use crate::tetrimino::PieceKind;
+
+// Not random at all: it deals the given pieces in order and starts over once at the end.
+// Useful to practice an opening or a specific situation again and again.
+#[derive(Clone)]
+pub struct Sequence {
+    pieces: Vec<PieceKind>,
+    position: usize,
+}
+
+impl Sequence {
+    // An empty sequence wouldn't be able to deal anything, so it's replaced by
+    // every piece, in the order of `PieceKind::ALL`.
+    pub fn new(pieces: Vec<PieceKind>) -> Sequence {
+        let pieces = if pieces.is_empty() { PieceKind::ALL.to_vec() } else { pieces };
+        Sequence { pieces, position: 0 }
+    }
+}
+
+impl Randomizer for Sequence {
+    fn next_piece(&mut self, _rng: &mut GameRng) -> PieceKind {
+        let piece = self.pieces[self.position];
+        self.position = (self.position + 1) % self.pieces.len();
+        piece
+    }
+}
@@ -0,0 +1,15 @@
+use rand::Rng;
+
+use crate::randomizer::Randomizer;
+use crate::rng::GameRng;
+use crate::tetrimino::PieceKind;
+
+// Every piece has the same chance to come, whatever came before. Pure luck.
+#[derive(Clone)]
+pub struct Uniform;
+
+impl Randomizer for Uniform {
+    fn next_piece(&mut self, rng: &mut GameRng) -> PieceKind {
+        *rng.choose(&PieceKind::ALL).expect("there are 7 pieces to pick")
+    }
+}
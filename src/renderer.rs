@@ -0,0 +1,433 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use tetris::{Clear, Game, GameEvent, GameMode, Tetrimino};
+use tetris::input::FRAMES_PER_SECOND;
+use tetris::timer::format_time;
+
+use crate::font::{draw_text, line_height};
+
+const TEXTURE_SIZE: u32 = 32;
+
+// The screen is split in three: the held piece and the stats on the left,
+// the game map in the middle and the upcoming pieces on the right.
+const LEFT_PANEL_X: i32 = 20;
+const BOARD_X: i32 = 180;
+const BOARD_Y: i32 = 40;
+// The room the game map has on the screen. A big game map gets smaller cells to fit in it.
+const BOARD_WIDTH: u32 = 320;
+const BOARD_HEIGHT: u32 = 540;
+const RIGHT_PANEL_X: i32 = 520;
+const PANEL_Y: i32 = 40;
+// The pieces shown in the panels are smaller than the ones on the game map.
+const PREVIEW_SIZE: u32 = TEXTURE_SIZE / 2;
+const TEXT_SCALE: u32 = 4;
+const SMALL_TEXT_SCALE: u32 = 2;
+
+// How dark a piece gets right before it locks, from 0 (not at all) to 255 (black).
+const MAX_LOCK_DARKNESS: u8 = 160;
+
+// How many frames the name of a clear (like "T-SPIN DOUBLE") stays on the screen.
+const CLEAR_LABEL_FRAMES: u32 = 90;
+// Same thing for the new level, shown over the game map.
+const LEVEL_UP_FRAMES: u32 = 90;
+// How dark the game map gets behind the summary at the end of the game.
+const SUMMARY_DARKNESS: u8 = 200;
+
+const BACKGROUND_COLOR: Color = Color { r: 30, g: 30, b: 40, a: 255 };
+const BOARD_COLOR: Color = Color { r: 0, g: 0, b: 0, a: 255 };
+const TEXT_COLOR: Color = Color { r: 255, g: 255, b: 255, a: 255 };
+
+// The tetriminos use the numbers 1 to 7 in their states to tell which color they are,
+// and the garbage uses 8. This table gives the color of each of them:
+// the color id `n` is at index `n - 1`.
+const PALETTE: [Color; 8] = [
+    Color { r: 0, g: 240, b: 240, a: 255 },
+    Color { r: 60, g: 136, b: 207, a: 255 },
+    Color { r: 240, g: 160, b: 0, a: 255 },
+    Color { r: 240, g: 240, b: 0, a: 255 },
+    Color { r: 73, g: 196, b: 137, a: 255 },
+    Color { r: 220, g: 40, b: 40, a: 255 },
+    Color { r: 160, g: 0, b: 240, a: 255 },
+    Color { r: 120, g: 120, b: 120, a: 255 },
+];
+
+// A grid of cells on the screen: where its top-left corner is and how big its cells are.
+// The game map is one, the next piece preview is another (with smaller cells).
+// The rows above `first_row` aren't drawn: this is how the hidden rows of the game map stay hidden.
+#[derive(Clone, Copy)]
+struct Grid {
+    x: i32,
+    y: i32,
+    cell_size: u32,
+    first_row: i32,
+}
+
+impl Grid {
+    // A grid showing everything, for the pieces of the panels.
+    fn panel(x: i32, y: i32) -> Grid {
+        Grid { x, y, cell_size: PREVIEW_SIZE, first_row: 0 }
+    }
+
+    // The grid of the visible part of the game map, with cells as big as they can be
+    // (but never bigger than the textures).
+    fn board(game: &Game) -> Grid {
+        let config = game.config();
+        let cell_size = TEXTURE_SIZE
+            .min(BOARD_WIDTH / config.width as u32)
+            .min(BOARD_HEIGHT / config.height as u32);
+        Grid { x: BOARD_X, y: BOARD_Y, cell_size, first_row: config.hidden_rows as i32 }
+    }
+
+    // Where the top-left corner of the (x, y) cell is on the screen.
+    fn cell(&self, x: i32, y: i32) -> (i32, i32) {
+        let size = self.cell_size as i32;
+        (self.x + x * size, self.y + (y - self.first_row) * size)
+    }
+}
+
+// The renderer owns one square texture per color id and knows how to draw a `Game` with them.
+pub struct Renderer<'a> {
+    textures: Vec<Texture<'a>>,
+    // The last clear worth showing and for how many more frames it's shown.
+    last_clear: Option<(Clear, u32)>,
+    // The same for the last level the game went to.
+    level_up: Option<(u32, u32)>,
+    // The game map as the renderer last saw it, for the invisible and fading stacks:
+    // the color of every cell next to the frame until which its block stays visible.
+    stack: Vec<(u8, u32)>,
+    // How many times `update` was called: the clock of the `stack`.
+    frame: u32,
+    // How many garbage lines came up so far, to push the `stack` up with them.
+    garbage_seen: u32,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(canvas: &mut Canvas<Window>,
+               texture_creator: &'a TextureCreator<WindowContext>) -> Renderer<'a> {
+        let textures = PALETTE
+            .iter()
+            .map(|&color| create_square_texture_rect(canvas, texture_creator, color, TEXTURE_SIZE)
+                .expect("failed to create square texture"))
+            .collect();
+        Renderer { textures, last_clear: None, level_up: None, stack: Vec::new(), frame: 0, garbage_seen: 0 }
+    }
+
+    // Looks at what happened during the frame to know what to show next.
+    pub fn update(&mut self, events: &[GameEvent], game: &Game) {
+        self.frame += 1;
+        self.update_stack(events, game);
+        self.last_clear = match self.last_clear {
+            Some((clear, frames)) if frames > 1 => Some((clear, frames - 1)),
+            _ => None,
+        };
+        self.level_up = match self.level_up {
+            Some((level, frames)) if frames > 1 => Some((level, frames - 1)),
+            _ => None,
+        };
+        for event in events {
+            match *event {
+                GameEvent::Scored(clear) => self.last_clear = Some((clear, CLEAR_LABEL_FRAMES)),
+                GameEvent::LevelUp(level) => self.level_up = Some((level, LEVEL_UP_FRAMES)),
+                _ => {}
+            }
+        }
+    }
+
+    // Moves the cells of the `stack` the same way the game map moved, so every block keeps
+    // its own time, then starts the clock of the blocks which just appeared.
+    fn update_stack(&mut self, events: &[GameEvent], game: &Game) {
+        let game_map = &game.tetris().game_map;
+        let width = game_map.width();
+        if self.stack.len() != width * game_map.height() {
+            self.stack = vec![(0, 0); width * game_map.height()];
+        }
+        // The garbage comes up before the piece locks.
+        let garbage = game.garbage_left() + game.garbage_cleared();
+        let pushed = garbage.saturating_sub(self.garbage_seen) as usize * game.config().scale();
+        let pushed = pushed.min(game_map.height());
+        self.garbage_seen = garbage;
+        self.stack.drain(0..pushed * width);
+        self.stack.extend(vec![(0, 0); pushed * width]);
+        if events.iter().any(|event| matches!(event, GameEvent::LinesCleared(_))) {
+            // From the bottom, so the lines above keep their number until they're removed.
+            for &y in game.cleared_rows().iter().rev() {
+                self.stack.drain(y * width..(y + 1) * width);
+            }
+            let removed = game.cleared_rows().len() * width;
+            self.stack.splice(0..0, vec![(0, 0); removed]);
+        }
+        let visible_until = self.frame.saturating_add(game.config().stack_visibility.visible_frames().unwrap_or(0));
+        for (y, line) in game_map.lines().enumerate() {
+            for (x, &color) in line.iter().enumerate() {
+                let cell = &mut self.stack[y * width + x];
+                if cell.0 != color {
+                    *cell = (color, visible_until);
+                }
+            }
+        }
+    }
+
+    // Whether the block at the given cell of the game map can be seen. The whole stack
+    // is shown again once the game is over.
+    fn is_visible(&self, game: &Game, x: usize, y: usize) -> bool {
+        if game.config().stack_visibility.visible_frames().is_none() || game.is_completed() || game.is_game_over() {
+            return true;
+        }
+        match self.stack.get(y * game.config().width + x) {
+            Some(&(_, visible_until)) => self.frame < visible_until,
+            None => true,
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, game: &Game) {
+        canvas.set_draw_color(BACKGROUND_COLOR);
+        canvas.clear();
+
+        let tetris = game.tetris();
+        let board = Grid::board(game);
+        let width = game.config().width as u32;
+        let height = game.config().height as u32;
+        canvas.set_draw_color(BOARD_COLOR);
+        canvas
+            .fill_rect(Rect::new(board.x, board.y, width * board.cell_size, height * board.cell_size))
+            .expect("could not draw the game map");
+
+        for (y, line) in tetris.game_map.lines().enumerate() {
+            for (x, &cell) in line.iter().enumerate() {
+                if self.is_visible(game, x, y) {
+                    self.draw_block(canvas, board, cell, x as i32, y as i32);
+                }
+            }
+        }
+        if let Some(ref piece) = tetris.current_piece {
+            // The ghost goes first, so the piece is drawn over it when they touch.
+            if let Some(ghost_y) = game.ghost_y() {
+                draw_ghost(canvas, board, piece, piece.x() as i32, ghost_y as i32);
+            }
+            self.draw_piece(canvas, board, piece, piece.x() as i32, piece.y() as i32);
+            // The closer the piece is to locking, the darker it gets.
+            let darkness = (game.lock_progress() * MAX_LOCK_DARKNESS as f32) as u8;
+            if darkness > 0 {
+                draw_shade(canvas, board, piece, piece.x() as i32, piece.y() as i32, darkness);
+            }
+        }
+
+        self.draw_left_panel(canvas, game);
+        self.draw_right_panel(canvas, game);
+
+        if game.is_completed() || game.is_game_over() {
+            draw_summary(canvas, board, game);
+        } else if let Some((level, _)) = self.level_up {
+            canvas.set_draw_color(TEXT_COLOR);
+            draw_text(canvas, &format!("LEVEL {}", level), board.x + 16, board.y + 160, TEXT_SCALE);
+        }
+    }
+
+    fn draw_left_panel(&self, canvas: &mut Canvas<Window>, game: &Game) {
+        let tetris = game.tetris();
+        let line = line_height(TEXT_SCALE);
+        let mut y = PANEL_Y;
+        canvas.set_draw_color(TEXT_COLOR);
+        draw_text(canvas, "HOLD", LEFT_PANEL_X, y, TEXT_SCALE);
+        if let Some(kind) = game.held_piece() {
+            let grid = Grid::panel(LEFT_PANEL_X, y + line);
+            self.draw_piece(canvas, grid, &Tetrimino::new(kind, &*game.config().rotation_system), 0, 0);
+        }
+        y += line + 5 * PREVIEW_SIZE as i32;
+
+        // Every mode shows what matters for its goal.
+        let stats = match game.config().mode {
+            GameMode::Endless => vec![("SCORE", tetris.score.to_string()),
+                                      ("LEVEL", tetris.current_level.to_string()),
+                                      ("LINES", tetris.nb_lines.to_string()),
+                                      ("GOAL", game.lines_to_next_level().to_string())],
+            GameMode::Marathon { lines } => vec![("SCORE", tetris.score.to_string()),
+                                                 ("LEVEL", tetris.current_level.to_string()),
+                                                 ("LINES", format!("{}/{}", tetris.nb_lines, lines)),
+                                                 ("GOAL", game.lines_to_next_level().to_string())],
+            GameMode::Sprint { lines } => vec![("TIME", format_time(game.time())),
+                                               ("LINES", format!("{}/{}", tetris.nb_lines, lines))],
+            GameMode::Dig { rise: None, .. } => vec![("TIME", format_time(game.time())),
+                                                     ("LEFT", game.garbage_left().to_string())],
+            GameMode::Dig { rise: Some(_), .. } => {
+                let frames = game.next_garbage_in().unwrap_or(0);
+                vec![("TIME", format_time(game.time())),
+                     ("DUG", game.garbage_cleared().to_string()),
+                     ("RISE", format!("{:.1}", frames as f32 / FRAMES_PER_SECOND as f32))]
+            }
+            GameMode::Ultra { .. } => vec![("TIME", format_time(game.remaining_time().unwrap_or_default())),
+                                           ("SCORE", tetris.score.to_string()),
+                                           ("LINES", tetris.nb_lines.to_string())],
+            GameMode::Zen => vec![("SCORE", tetris.score.to_string()),
+                                  ("LINES", tetris.nb_lines.to_string()),
+                                  ("UNDO", game.undo_count().to_string()),
+                                  ("GRAVITY", if game.gravity_enabled() { "ON" } else { "OFF" }.to_owned())],
+        };
+        canvas.set_draw_color(TEXT_COLOR);
+        for (label, value) in stats {
+            draw_text(canvas, label, LEFT_PANEL_X, y, TEXT_SCALE);
+            draw_text(canvas, &value, LEFT_PANEL_X, y + line, TEXT_SCALE);
+            y += line * 3;
+        }
+
+        let small_line = line_height(SMALL_TEXT_SCALE);
+        if let GameMode::Sprint { .. } = game.config().mode {
+            for (index, split) in game.splits().iter().enumerate() {
+                let lines = (index + 1) * 10;
+                draw_text(canvas, &format!("{} {}", lines, format_time(*split)), LEFT_PANEL_X, y, SMALL_TEXT_SCALE);
+                y += small_line;
+            }
+            y += small_line;
+        }
+
+        // The panel is narrow, so every word of the clear gets its own line.
+        if let Some((clear, _)) = self.last_clear {
+            let mut texts = clear.label().split(' ').map(String::from).collect::<Vec<_>>();
+            if clear.combo > 0 {
+                texts.push(format!("COMBO {}", clear.combo));
+            }
+            texts.push(format!("+{}", clear.points));
+            for text in texts {
+                draw_text(canvas, &text, LEFT_PANEL_X, y, SMALL_TEXT_SCALE);
+                y += small_line;
+            }
+        }
+    }
+
+    fn draw_right_panel(&self, canvas: &mut Canvas<Window>, game: &Game) {
+        let line = line_height(TEXT_SCALE);
+        let mut y = PANEL_Y;
+        canvas.set_draw_color(TEXT_COLOR);
+        if game.config().preview_count > 0 {
+            draw_text(canvas, "NEXT", RIGHT_PANEL_X, y, TEXT_SCALE);
+            y += line;
+            // Every piece is drawn in its spawn state, the way it'll look when it comes in.
+            for kind in game.preview() {
+                let grid = Grid::panel(RIGHT_PANEL_X, y);
+                self.draw_piece(canvas, grid, &Tetrimino::new(kind, &*game.config().rotation_system), 0, 0);
+                y += 4 * PREVIEW_SIZE as i32;
+            }
+            y += PREVIEW_SIZE as i32;
+        }
+
+        // The seed can be long, so it's written smaller than the rest.
+        canvas.set_draw_color(TEXT_COLOR);
+        draw_text(canvas, "SEED", RIGHT_PANEL_X, y, TEXT_SCALE);
+        draw_text(canvas, &game.seed().to_string(), RIGHT_PANEL_X, y + line, SMALL_TEXT_SCALE);
+    }
+
+    // Draws the blocks of a tetrimino with its top-left corner at the (x, y) cell of the grid.
+    fn draw_piece(&self, canvas: &mut Canvas<Window>, grid: Grid, piece: &Tetrimino, x: i32, y: i32) {
+        for (decal_y, line) in piece.shape().iter().enumerate() {
+            for (decal_x, &cell) in line.iter().enumerate() {
+                self.draw_block(canvas, grid, cell, x + decal_x as i32, y + decal_y as i32);
+            }
+        }
+    }
+
+    // Draws one block of the given color id. Zero means there is no block, so nothing is drawn.
+    // Neither is a block in a hidden row.
+    fn draw_block(&self, canvas: &mut Canvas<Window>, grid: Grid, cell: u8, x: i32, y: i32) {
+        if cell == 0 || y < grid.first_row {
+            return;
+        }
+        let size = grid.cell_size;
+        let (left, top) = grid.cell(x, y);
+        canvas
+            .copy(&self.textures[cell as usize - 1], None, Rect::new(left, top, size, size))
+            .expect("could not copy texture into window");
+    }
+}
+
+// The ghost is only the outline of the blocks, in the color of the piece,
+// so it can't be mistaken for the real one.
+fn draw_ghost(canvas: &mut Canvas<Window>, grid: Grid, piece: &Tetrimino, x: i32, y: i32) {
+    let size = grid.cell_size;
+    for (decal_y, line) in piece.shape().iter().enumerate() {
+        for (decal_x, &cell) in line.iter().enumerate() {
+            let y = y + decal_y as i32;
+            if cell == 0 || y < grid.first_row {
+                continue;
+            }
+            let (left, top) = grid.cell(x + decal_x as i32, y);
+            canvas.set_draw_color(PALETTE[cell as usize - 1]);
+            canvas
+                .draw_rect(Rect::new(left + 1, top + 1, size - 2, size - 2))
+                .expect("could not draw the ghost");
+        }
+    }
+}
+
+// Once the game is over, the game map is darkened and covered with what the player did:
+// the score, the lines, the time and how many clears of every kind.
+fn draw_summary(canvas: &mut Canvas<Window>, grid: Grid, game: &Game) {
+    let config = game.config();
+    let width = config.width as u32 * grid.cell_size;
+    let height = config.height as u32 * grid.cell_size;
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, SUMMARY_DARKNESS));
+    canvas
+        .fill_rect(Rect::new(grid.x, grid.y, width, height))
+        .expect("could not darken the game map");
+    canvas.set_blend_mode(BlendMode::None);
+
+    let x = grid.x + 16;
+    let mut y = grid.y + 16;
+    let title = if game.is_completed() { "COMPLETE" } else { "GAME OVER" };
+    canvas.set_draw_color(TEXT_COLOR);
+    draw_text(canvas, title, x, y, TEXT_SCALE);
+    y += line_height(TEXT_SCALE) * 2;
+
+    let tetris = game.tetris();
+    let small_line = line_height(SMALL_TEXT_SCALE);
+    let mut texts = vec![format!("SCORE {}", tetris.score),
+                         format!("LINES {}", tetris.nb_lines),
+                         format!("LEVEL {}", tetris.current_level),
+                         format!("TIME {}", format_time(game.time())),
+                         String::new()];
+    texts.extend(game.clear_stats().breakdown().into_iter().map(|(name, count)| format!("{} {}", name, count)));
+    texts.push(String::new());
+    texts.push("ESC TO QUIT".to_owned());
+    for text in texts {
+        draw_text(canvas, &text, x, y, SMALL_TEXT_SCALE);
+        y += small_line;
+    }
+}
+
+// Covers the blocks of a piece with a translucent black square.
+fn draw_shade(canvas: &mut Canvas<Window>, grid: Grid, piece: &Tetrimino, x: i32, y: i32, alpha: u8) {
+    let size = grid.cell_size;
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, alpha));
+    for (decal_y, line) in piece.shape().iter().enumerate() {
+        for (decal_x, &cell) in line.iter().enumerate() {
+            let y = y + decal_y as i32;
+            if cell != 0 && y >= grid.first_row {
+                let (left, top) = grid.cell(x + decal_x as i32, y);
+                canvas
+                    .fill_rect(Rect::new(left, top, size, size))
+                    .expect("could not shade the piece");
+            }
+        }
+    }
+    canvas.set_blend_mode(BlendMode::None);
+}
+
+fn create_square_texture_rect<'a>(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    color: Color,
+    size: u32,
+) -> Option<Texture<'a>> {
+    if let Ok(mut square_texture) = texture_creator
+        .create_texture_target(None, size, size) {
+        canvas.with_texture_canvas(&mut square_texture, |texture| {
+            texture.set_draw_color(color);
+            texture.clear()
+        }).expect("failed to color a texture");
+        Some(square_texture)
+    } else { None }
+}
@@ -0,0 +1,21 @@
+use rand::{SeedableRng, XorShiftRng};
+
+// The random number generator every game owns. Given the same seed,
+// it gives the same numbers, so a game can be played again piece for piece.
+pub type GameRng = XorShiftRng;
+
+// A xorshift generator only needs 128 bits of seed, but they can't all be zero.
+// We spread the 64-bit seed over them with splitmix64, which never gives four zeros in a row,
+// so any seed (including 0) is fine.
+pub fn rng_from_seed(seed: u64) -> GameRng {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    let (a, b) = (next(), next());
+    XorShiftRng::from_seed([a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32])
+}
@@ -0,0 +1,188 @@
+use crate::board::Board;
+use crate::rotation::RotationSystem;
+use crate::tetrimino::{PieceKind, Tetrimino};
+
+// The Arika Rotation System, used by the TGM series.
+// The tetriminos spawn flat side up and stick to the bottom of their box when they rotate.
+// When a rotation doesn't fit, the piece tries one cell to the right, then one to the left.
+pub struct Ars;
+
+impl Ars {
+    // The J, L and T tetriminos have an exception: if the first block in the way
+    // (reading the new state from left to right, top to bottom) is in the middle column,
+    // the piece isn't kicked at all. Without it, these pieces could climb where they shouldn't.
+    fn blocked_by_center_column(piece: &Tetrimino, game_map: &Board, new_state: usize) -> bool {
+        for (decal_y, line) in piece.state(new_state).iter().enumerate() {
+            for (decal_x, &cell) in line.iter().enumerate() {
+                let x = piece.x() + decal_x as isize;
+                let y = piece.y() + decal_y as isize;
+                // The walls don't count here, only the blocks of the stack.
+                let blocked = !matches!(game_map.get(x, y), None | Some(0));
+                if cell != 0 && blocked {
+                    return decal_x / piece.scale() == 1;
+                }
+            }
+        }
+        false
+    }
+}
+
+impl RotationSystem for Ars {
+    fn name(&self) -> &'static str {
+        "ARS"
+    }
+
+    fn states(&self, kind: PieceKind) -> Vec<Vec<Vec<u8>>> {
+        match kind {
+            PieceKind::I =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![1, 1, 1, 1],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![1, 1, 1, 1],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                     ],
+                ],
+            PieceKind::J =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 2, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![2, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![2, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 2, 0],
+                          vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::L =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![3, 3, 3, 0],
+                          vec![3, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![3, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![0, 0, 3, 0],
+                          vec![3, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::O =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::S =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![5, 0, 0, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![5, 0, 0, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::Z =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![6, 6, 0, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 6, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 6, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![6, 6, 0, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 6, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 6, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::T =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 0, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![0, 7, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![0, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+        }
+    }
+
+    fn kicks(&self, piece: &Tetrimino, game_map: &Board, new_state: usize) -> Vec<(isize, isize)> {
+        match piece.kind() {
+            // The I tetrimino never kicks, the O one doesn't need to.
+            PieceKind::I | PieceKind::O => vec![(0, 0)],
+            PieceKind::J | PieceKind::L | PieceKind::T
+            if Ars::blocked_by_center_column(piece, game_map, new_state) => vec![(0, 0)],
+            _ => vec![(0, 0), (1, 0), (-1, 0)],
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::board::Board;
+use crate::rotation::RotationSystem;
+use crate::tetrimino::{PieceKind, Tetrimino};
+
+// The way this game used to rotate the tetriminos, before the other systems came in.
+// The pieces stick to the top-left corner of their box and, when a rotation doesn't fit,
+// they try to move along the `x` axis only.
+pub struct Legacy;
+
+impl RotationSystem for Legacy {
+    fn name(&self) -> &'static str {
+        "Legacy"
+    }
+
+    fn states(&self, kind: PieceKind) -> Vec<Vec<Vec<u8>>> {
+        match kind {
+            PieceKind::I =>
+                vec![vec![vec![1, 1, 1, 1],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![1, 0, 0, 0],
+                          vec![1, 0, 0, 0],
+                          vec![1, 0, 0, 0],
+                          vec![1, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::J =>
+                vec![vec![vec![2, 2, 2, 0],
+                          vec![0, 0, 2, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![2, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![2, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![2, 2, 0, 0],
+                          vec![2, 0, 0, 0],
+                          vec![2, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::L =>
+                vec![vec![vec![3, 3, 3, 0],
+                          vec![3, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![3, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 3, 0],
+                          vec![3, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![3, 0, 0, 0],
+                          vec![3, 0, 0, 0],
+                          vec![3, 3, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::O =>
+                vec![vec![vec![4, 4, 0, 0],
+                          vec![4, 4, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::S =>
+                vec![vec![vec![0, 5, 5, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 5, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![0, 0, 5, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::Z =>
+                vec![vec![vec![6, 6, 0, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 6, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 6, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::T =>
+                vec![vec![vec![7, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 0, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![0, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+        }
+    }
+
+    fn kicks(&self, _piece: &Tetrimino, _game_map: &Board, _new_state: usize) -> Vec<(isize, isize)> {
+        // In case the piece cannot be placed where we want, we try to move it on the `x` axis
+        // to see if it'd work in some other place. It allows you to have a Tetris
+        // that is much more flexible and comfortable to play
+        [0, -1, 1, -2, 2, -3].iter().map(|&x| (x, 0)).collect()
+    }
+}
@@ -0,0 +1,42 @@
+use crate::board::Board;
+use crate::tetrimino::{PieceKind, Tetrimino};
+
+mod ars;
+mod legacy;
+mod nes;
+mod srs;
+
+pub use self::ars::Ars;
+pub use self::legacy::Legacy;
+pub use self::nes::Nes;
+pub use self::srs::Srs;
+
+// Every player community has its own idea of how a tetrimino should rotate:
+// what it looks like when it spawns, what its states are and where it can go when
+// the rotation doesn't fit. A rotation system owns all of these rules.
+//
+// The states are 4x4 grids (just like the ones `Tetrimino` stores), listed in clockwise order.
+pub trait RotationSystem: Send + Sync {
+    // The name shown to the players.
+    fn name(&self) -> &'static str;
+
+    // The states of the given tetrimino, in clockwise order.
+    fn states(&self, kind: PieceKind) -> Vec<Vec<Vec<u8>>>;
+
+    // The state the tetrimino is in when it appears at the top of the game map.
+    fn spawn_state(&self, _kind: PieceKind) -> usize {
+        0
+    }
+
+    // The (x, y) shifts to try, in order, when `piece` rotates into `new_state`
+    // and the first one which fits wins. The first shift usually is (0, 0): no kick at all.
+    //
+    // Unlike the guideline kick tables, a positive `y` means down, like in the game map.
+    fn kicks(&self, piece: &Tetrimino, game_map: &Board, new_state: usize) -> Vec<(isize, isize)>;
+
+    // Whether a T-spin mini done with the given kick (its index in what `kicks` returned)
+    // counts as a full T-spin anyway.
+    fn promotes_t_spin_mini(&self, _kick: usize) -> bool {
+        false
+    }
+}
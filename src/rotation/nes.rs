@@ -0,0 +1,131 @@
+use crate::board::Board;
+use crate::rotation::RotationSystem;
+use crate::tetrimino::{PieceKind, Tetrimino};
+
+// The rotation of the NES version: the I, S and Z tetriminos only have two states,
+// the O one only has one, and a rotation which doesn't fit simply doesn't happen.
+pub struct Nes;
+
+impl RotationSystem for Nes {
+    fn name(&self) -> &'static str {
+        "NES"
+    }
+
+    fn states(&self, kind: PieceKind) -> Vec<Vec<Vec<u8>>> {
+        match kind {
+            PieceKind::I =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![1, 1, 1, 1],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                     ],
+                ],
+            PieceKind::J =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 2, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![2, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![2, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 2, 0],
+                          vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::L =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![3, 3, 3, 0],
+                          vec![3, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![3, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 3, 0],
+                          vec![3, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::O =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::S =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 5, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![0, 0, 5, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::Z =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![6, 6, 0, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 6, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 6, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::T =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 0, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![0, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+        }
+    }
+
+    fn kicks(&self, _piece: &Tetrimino, _game_map: &Board, _new_state: usize) -> Vec<(isize, isize)> {
+        vec![(0, 0)]
+    }
+}
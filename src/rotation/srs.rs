@@ -0,0 +1,285 @@
+use crate::board::Board;
+use crate::rotation::RotationSystem;
+use crate::tetrimino::{PieceKind, Tetrimino};
+
+// When a rotation doesn't fit where the piece is, SRS tries up to four other positions
+// before giving up. These are the "wall kicks": the offsets to try, in order,
+// for each pair of (current state -> new state).
+//
+// Careful: just like in the guideline, a positive `y` means up here, whereas in the game map
+// a positive `y` means down. This is why `y` gets negated when we hand them out.
+//
+// The J, L, S, T and Z tetriminos share the same table.
+const JLSTZ_KICKS: [[(isize, isize); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+// The I tetrimino is longer than the others, so it gets its own table.
+const I_KICKS: [[(isize, isize); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],  // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],  // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],  // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],  // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],  // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],  // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],  // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],  // 0 -> L
+];
+
+// Returns the index of the (from -> to) pair in the kick tables above.
+fn kick_index(from: usize, to: usize) -> usize {
+    match (from, to) {
+        (0, 1) => 0,
+        (1, 0) => 1,
+        (1, 2) => 2,
+        (2, 1) => 3,
+        (2, 3) => 4,
+        (3, 2) => 5,
+        (3, 0) => 6,
+        (0, 3) => 7,
+        _ => unreachable!(),
+    }
+}
+
+// The Super Rotation System of the Tetris guideline: every tetrimino has four states,
+// spawns flat side down and gets kicked around following the tables above.
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    fn name(&self) -> &'static str {
+        "SRS"
+    }
+
+    fn states(&self, kind: PieceKind) -> Vec<Vec<Vec<u8>>> {
+        match kind {
+            PieceKind::I =>
+                vec![vec![vec![0, 0, 0, 0],
+                          vec![1, 1, 1, 1],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                          vec![0, 0, 1, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![1, 1, 1, 1],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 1, 0, 0],
+                          vec![0, 1, 0, 0],
+                          vec![0, 1, 0, 0],
+                          vec![0, 1, 0, 0],
+                     ],
+                ],
+            PieceKind::J =>
+                vec![vec![vec![2, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 2, 0],
+                          vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![2, 2, 2, 0],
+                          vec![0, 0, 2, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 2, 0, 0],
+                          vec![0, 2, 0, 0],
+                          vec![2, 2, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::L =>
+                vec![vec![vec![0, 0, 3, 0],
+                          vec![3, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 3, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![3, 3, 3, 0],
+                          vec![3, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![3, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 3, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::O =>
+                vec![vec![vec![0, 4, 4, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 4, 4, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 4, 4, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 4, 4, 0],
+                          vec![0, 4, 4, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::S =>
+                vec![vec![vec![0, 5, 5, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 5, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![0, 0, 5, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![0, 5, 5, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![5, 0, 0, 0],
+                          vec![5, 5, 0, 0],
+                          vec![0, 5, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::Z =>
+                vec![vec![vec![6, 6, 0, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 6, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 6, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![6, 6, 0, 0],
+                          vec![0, 6, 6, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 6, 0, 0],
+                          vec![6, 6, 0, 0],
+                          vec![6, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+            PieceKind::T =>
+                vec![vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 0, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![0, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 0, 0, 0],
+                          vec![7, 7, 7, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                     vec![vec![0, 7, 0, 0],
+                          vec![7, 7, 0, 0],
+                          vec![0, 7, 0, 0],
+                          vec![0, 0, 0, 0],
+                     ],
+                ],
+        }
+    }
+
+    fn kicks(&self, piece: &Tetrimino, _game_map: &Board, new_state: usize) -> Vec<(isize, isize)> {
+        // The O tetrimino looks the same whatever its state, so it never needs to be kicked.
+        let kicks = match piece.kind() {
+            PieceKind::O => return vec![(0, 0)],
+            PieceKind::I => &I_KICKS[kick_index(piece.current_state(), new_state)],
+            _ => &JLSTZ_KICKS[kick_index(piece.current_state(), new_state)],
+        };
+        kicks.iter().map(|&(x, y)| (x, -y)).collect()
+    }
+
+    // The last kick is the one which lets the T go down two rows, in the "T-spin triple"
+    // and "fin" setups. These are always full T-spins, whatever the corners say.
+    fn promotes_t_spin_mini(&self, kick: usize) -> bool {
+        kick == 4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetrimino::Rotation;
+
+    #[test]
+    fn kicks_go_both_ways() {
+        // Going back undoes the kick: every table is the opposite of the one going the other way.
+        for &(table, from, to) in [(&JLSTZ_KICKS, 0, 1), (&I_KICKS, 0, 1), (&JLSTZ_KICKS, 2, 3),
+                                   (&I_KICKS, 3, 0), (&JLSTZ_KICKS, 1, 2), (&I_KICKS, 2, 3)].iter() {
+            let there = table[kick_index(from, to)];
+            let back = table[kick_index(to, from)];
+            for (&(x, y), &(back_x, back_y)) in there.iter().zip(back.iter()) {
+                assert_eq!((x, y), (-back_x, -back_y));
+            }
+        }
+    }
+
+    #[test]
+    fn kicks_point_down_in_the_game_map() {
+        let piece = Tetrimino::new(PieceKind::T, &Srs);
+        let game_map = Board::new(10, 22);
+        assert_eq!(Srs.kicks(&piece, &game_map, 1), vec![(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]);
+        let piece = Tetrimino::new(PieceKind::O, &Srs);
+        assert_eq!(Srs.kicks(&piece, &game_map, 1), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn i_kicks_off_the_right_wall() {
+        let game_map = Board::new(10, 22);
+        let mut piece = Tetrimino::new(PieceKind::I, &Srs);
+        assert!(piece.change_position(&game_map, 3, 5));
+        assert_eq!(piece.rotate(&game_map, Rotation::Clockwise, &Srs), Some(0));
+        // Standing against the right wall, lying down again has to move it to the left.
+        assert!(piece.change_position(&game_map, 7, 5));
+        assert_eq!(piece.rotate(&game_map, Rotation::Clockwise, &Srs), Some(1));
+        assert_eq!((piece.x(), piece.y(), piece.current_state()), (6, 5, 2));
+    }
+
+    #[test]
+    fn t_kicks_up_from_the_floor() {
+        // A T pointing up, lying on the floor: standing up would go through the floor,
+        // so it ends up one row higher (and one column to the left).
+        let game_map = Board::new(10, 4);
+        let mut piece = Tetrimino::new(PieceKind::T, &Srs);
+        assert!(piece.change_position(&game_map, 3, 2));
+        assert_eq!(piece.rotate(&game_map, Rotation::Clockwise, &Srs), Some(2));
+        assert_eq!((piece.x(), piece.y(), piece.current_state()), (2, 1, 1));
+    }
+}
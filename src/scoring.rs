@@ -0,0 +1,390 @@
+use crate::board::Board;
+use crate::rotation::RotationSystem;
+use crate::tetrimino::{PieceKind, Tetrimino};
+
+// The points of the guideline, before being multiplied by the level.
+// Each table is indexed by the number of lines cleared.
+const LINE_CLEAR_POINTS: [u32; 5] = [0, 100, 300, 500, 800];
+const T_SPIN_MINI_POINTS: [u32; 5] = [100, 200, 400, 400, 400];
+const T_SPIN_POINTS: [u32; 5] = [400, 800, 1200, 1600, 1600];
+const PERFECT_CLEAR_POINTS: [u32; 5] = [0, 800, 1200, 1800, 2000];
+// A perfect clear made with a back-to-back tetris is worth even more.
+const BACK_TO_BACK_PERFECT_CLEAR_POINTS: u32 = 3200;
+// Every clear in a row (after the first one) adds this much.
+const COMBO_POINTS: u32 = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TSpin {
+    None,
+    Mini,
+    Full,
+}
+
+// Everything about the way a piece locked which is worth points:
+// the game sends one with `GameEvent::Scored` so the HUD can celebrate it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clear {
+    pub lines: u32,
+    pub t_spin: TSpin,
+    // A "difficult" clear (a tetris or a T-spin clearing lines) right after another one.
+    pub back_to_back: bool,
+    // How many clears in a row came before this one: 0 for the first one.
+    pub combo: u32,
+    // Nothing is left on the game map.
+    pub perfect_clear: bool,
+    pub points: u32,
+}
+
+impl Clear {
+    // What the HUD shows, such as "B2B T-SPIN DOUBLE".
+    pub fn label(&self) -> String {
+        let mut words = Vec::new();
+        if self.back_to_back {
+            words.push("B2B");
+        }
+        match self.t_spin {
+            TSpin::Mini => words.push("MINI T-SPIN"),
+            TSpin::Full => words.push("T-SPIN"),
+            TSpin::None => {}
+        }
+        match self.lines {
+            1 => words.push("SINGLE"),
+            2 => words.push("DOUBLE"),
+            3 => words.push("TRIPLE"),
+            4 if self.t_spin == TSpin::None => words.push("TETRIS"),
+            4 => words.push("QUAD"),
+            _ => {}
+        }
+        if self.perfect_clear {
+            words.push("PERFECT CLEAR");
+        }
+        words.join(" ")
+    }
+
+    // How many lines this counts for with the variable goal: the points of the clear
+    // divided by 100, before the level, the combo and the perfect clear come in.
+    // A tetris is worth 8 lines, a T-spin double 12 and so on.
+    pub fn awarded_lines(&self) -> u32 {
+        let lines = base_points(self.lines, self.t_spin) / 100;
+        if self.back_to_back {
+            lines + lines / 2
+        } else {
+            lines
+        }
+    }
+
+    // Whether this clear keeps the back-to-back chain going.
+    fn is_difficult(&self) -> bool {
+        self.lines == 4 || (self.lines > 0 && self.t_spin != TSpin::None)
+    }
+}
+
+// How many clears of every kind the player did during a game, for the summary at the end.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClearStats {
+    // Indexed by the kind of T-spin (none, mini, full) then by the number of lines.
+    counts: [[u32; 5]; 3],
+    pub back_to_backs: u32,
+    pub perfect_clears: u32,
+    pub max_combo: u32,
+}
+
+impl ClearStats {
+    // How many times the player cleared that many lines with that kind of T-spin.
+    pub fn count(&self, lines: u32, t_spin: TSpin) -> u32 {
+        self.counts[t_spin_index(t_spin)][lines.min(4) as usize]
+    }
+
+    // Every kind of clear which happened at least once, named like on the HUD,
+    // the plain line clears first.
+    pub fn breakdown(&self) -> Vec<(String, u32)> {
+        let mut breakdown = Vec::new();
+        for &t_spin in [TSpin::None, TSpin::Mini, TSpin::Full].iter() {
+            for lines in 0..5 {
+                let count = self.count(lines, t_spin);
+                if count > 0 {
+                    let clear = Clear {
+                        lines,
+                        t_spin,
+                        back_to_back: false,
+                        combo: 0,
+                        perfect_clear: false,
+                        points: 0,
+                    };
+                    breakdown.push((clear.label(), count));
+                }
+            }
+        }
+        for &(name, count) in [("B2B", self.back_to_backs),
+                               ("PERFECT CLEAR", self.perfect_clears),
+                               ("MAX COMBO", self.max_combo)].iter() {
+            if count > 0 {
+                breakdown.push((name.to_owned(), count));
+            }
+        }
+        breakdown
+    }
+
+    fn add(&mut self, clear: &Clear) {
+        self.counts[t_spin_index(clear.t_spin)][clear.lines.min(4) as usize] += 1;
+        if clear.back_to_back {
+            self.back_to_backs += 1;
+        }
+        if clear.perfect_clear {
+            self.perfect_clears += 1;
+        }
+        self.max_combo = self.max_combo.max(clear.combo);
+    }
+}
+
+fn t_spin_index(t_spin: TSpin) -> usize {
+    match t_spin {
+        TSpin::None => 0,
+        TSpin::Mini => 1,
+        TSpin::Full => 2,
+    }
+}
+
+// Keeps track of what the scoring needs to remember from one piece to the next.
+#[derive(Clone)]
+pub(crate) struct Scoring {
+    // How many clears in a row, `None` when the last piece didn't clear anything.
+    combo: Option<u32>,
+    back_to_back: bool,
+    stats: ClearStats,
+}
+
+impl Scoring {
+    pub(crate) fn new() -> Scoring {
+        Scoring { combo: None, back_to_back: false, stats: ClearStats::default() }
+    }
+
+    pub(crate) fn stats(&self) -> &ClearStats {
+        &self.stats
+    }
+
+    // Called every time a piece locks. Returns what it was worth, or `None`
+    // when it was just a piece being put down like any other.
+    pub(crate) fn lock(&mut self, lines: u32, t_spin: TSpin, perfect_clear: bool, level: u32) -> Option<Clear> {
+        if lines == 0 {
+            self.combo = None;
+            if t_spin == TSpin::None {
+                return None;
+            }
+        } else {
+            self.combo = Some(self.combo.map_or(0, |combo| combo + 1));
+        }
+
+        let mut clear = Clear {
+            lines,
+            t_spin,
+            back_to_back: false,
+            combo: self.combo.unwrap_or(0),
+            perfect_clear,
+            points: 0,
+        };
+        let mut points = base_points(lines, t_spin);
+        // A T-spin without any line doesn't break the chain, but doesn't add to it either.
+        if lines > 0 {
+            let difficult = clear.is_difficult();
+            clear.back_to_back = difficult && self.back_to_back;
+            self.back_to_back = difficult;
+        }
+        if clear.back_to_back {
+            points += points / 2;
+        }
+        points = points.saturating_add(COMBO_POINTS.saturating_mul(clear.combo));
+        if perfect_clear {
+            points = points.saturating_add(if clear.back_to_back && lines == 4 {
+                BACK_TO_BACK_PERFECT_CLEAR_POINTS
+            } else {
+                PERFECT_CLEAR_POINTS[lines.min(4) as usize]
+            });
+        }
+        // A very long game could go past what a `u32` holds: the points stop there.
+        clear.points = points.saturating_mul(level);
+        self.stats.add(&clear);
+        Some(clear)
+    }
+}
+
+// The points of a clear before anything else comes in.
+fn base_points(lines: u32, t_spin: TSpin) -> u32 {
+    let index = lines.min(4) as usize;
+    match t_spin {
+        TSpin::None => LINE_CLEAR_POINTS[index],
+        TSpin::Mini => T_SPIN_MINI_POINTS[index],
+        TSpin::Full => T_SPIN_POINTS[index],
+    }
+}
+
+// Tells whether the piece which is about to lock did a T-spin, using the 3-corner rule:
+// the last thing the player did has to be a rotation (`last_kick` is the kick it used),
+// and at least 3 of the 4 cells diagonal to the center of the T have to be taken
+// (the walls and the floor count). It's a full T-spin when both corners the T points to
+// are taken, otherwise it's only a mini.
+pub(crate) fn detect_t_spin(piece: &Tetrimino,
+                            game_map: &Board,
+                            last_kick: Option<usize>,
+                            rotation_system: &dyn RotationSystem) -> TSpin {
+    let kick = match last_kick {
+        Some(kick) if piece.kind() == PieceKind::T => kick,
+        _ => return TSpin::None,
+    };
+    let shape = piece.shape();
+    let is_block = |x: isize, y: isize| {
+        x >= 0 && y >= 0 && (y as usize) < shape.len() && (x as usize) < shape[y as usize].len()
+            && shape[y as usize][x as usize] != 0
+    };
+    // The center of the T is the only block with 3 neighbors. The side without a neighbor
+    // is the back of the T: it points the other way.
+    let directions = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+    let mut center = None;
+    for y in 0..shape.len() as isize {
+        for x in 0..shape[y as usize].len() as isize {
+            if !is_block(x, y) {
+                continue;
+            }
+            let missing = directions.iter().filter(|&&(dx, dy)| !is_block(x + dx, y + dy));
+            let missing = missing.cloned().collect::<Vec<_>>();
+            if missing.len() == 1 {
+                center = Some((x, y, missing[0]));
+            }
+        }
+    }
+    let (center_x, center_y, (back_x, back_y)) = match center {
+        Some(center) => center,
+        None => return TSpin::None,
+    };
+
+    let is_taken = |decal_x: isize, decal_y: isize| {
+        let x = piece.x() + center_x + decal_x;
+        let y = piece.y() + center_y + decal_y;
+        game_map.get(x, y) != Some(0)
+    };
+    let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+    let taken = corners.iter().filter(|&&(x, y)| is_taken(x, y)).count();
+    if taken < 3 {
+        return TSpin::None;
+    }
+    // The front corners are on the opposite side of the back, one on each side.
+    let (front_x, front_y) = (-back_x, -back_y);
+    let front = [(front_x + front_y, front_y + front_x), (front_x - front_y, front_y - front_x)];
+    if front.iter().all(|&(x, y)| is_taken(x, y)) || rotation_system.promotes_t_spin_mini(kick) {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::Srs;
+    use crate::tetrimino::Rotation;
+
+    // A T in the given state with the top-left corner of its grid at (x, y).
+    // It's turned around in an empty game map first, so no kick gets in the way.
+    fn t_piece(game_map: &Board, state: usize, x: isize, y: isize) -> Tetrimino {
+        let empty = Board::new(10, 10);
+        let mut piece = Tetrimino::new(PieceKind::T, &Srs);
+        assert!(piece.change_position(&empty, 3, 3));
+        for _ in 0..state {
+            assert_eq!(piece.rotate(&empty, Rotation::Clockwise, &Srs), Some(0));
+        }
+        assert!(piece.change_position(game_map, x, y));
+        piece
+    }
+
+    // Fills a line of the game map, except for the given columns.
+    fn fill_row(game_map: &mut Board, y: usize, holes: &[usize]) {
+        for x in 0..game_map.width() {
+            if !holes.contains(&x) {
+                game_map.set(x, y, 8);
+            }
+        }
+    }
+
+    // The usual T-spin double slot: the T points down into it,
+    // with a block hanging over its left side.
+    fn t_spin_double_slot() -> Board {
+        let mut game_map = Board::new(10, 6);
+        fill_row(&mut game_map, 5, &[4]);
+        fill_row(&mut game_map, 4, &[3, 4, 5]);
+        game_map.set(3, 3, 8);
+        game_map
+    }
+
+    #[test]
+    fn t_spin_double() {
+        let game_map = t_spin_double_slot();
+        let piece = t_piece(&game_map, 2, 3, 3);
+        assert_eq!(detect_t_spin(&piece, &game_map, Some(0), &Srs), TSpin::Full);
+    }
+
+    #[test]
+    fn no_t_spin_without_a_rotation() {
+        let game_map = t_spin_double_slot();
+        let piece = t_piece(&game_map, 2, 3, 3);
+        assert_eq!(detect_t_spin(&piece, &game_map, None, &Srs), TSpin::None);
+    }
+
+    #[test]
+    fn t_spin_mini() {
+        // The T points up on the floor: both corners behind it are taken,
+        // but only one of the two in front of it.
+        let mut game_map = Board::new(10, 6);
+        game_map.set(3, 4, 8);
+        let piece = t_piece(&game_map, 0, 3, 4);
+        assert_eq!(detect_t_spin(&piece, &game_map, Some(0), &Srs), TSpin::Mini);
+        // Unless the last kick of SRS was used.
+        assert_eq!(detect_t_spin(&piece, &game_map, Some(4), &Srs), TSpin::Full);
+        // Two corners aren't enough.
+        let piece = t_piece(&Board::new(10, 6), 0, 3, 4);
+        assert_eq!(detect_t_spin(&piece, &Board::new(10, 6), Some(0), &Srs), TSpin::None);
+    }
+
+    #[test]
+    fn t_spin_double_points() {
+        let mut scoring = Scoring::new();
+        let clear = scoring.lock(2, TSpin::Full, false, 1).expect("a T-spin double is worth points");
+        assert_eq!(clear.points, 1200);
+        assert!(!clear.back_to_back);
+        assert_eq!(clear.label(), "T-SPIN DOUBLE");
+        // The same at level 3.
+        assert_eq!(Scoring::new().lock(2, TSpin::Full, false, 3).map(|clear| clear.points), Some(3600));
+    }
+
+    #[test]
+    fn back_to_back_tetris() {
+        let mut scoring = Scoring::new();
+        assert_eq!(scoring.lock(4, TSpin::None, false, 1).map(|clear| clear.points), Some(800));
+        // A piece without any line breaks the combo, but not the back-to-back.
+        assert_eq!(scoring.lock(0, TSpin::None, false, 1), None);
+        let clear = scoring.lock(4, TSpin::None, false, 1).expect("a tetris is worth points");
+        assert!(clear.back_to_back);
+        assert_eq!(clear.combo, 0);
+        assert_eq!(clear.points, 1200);
+        assert_eq!(clear.label(), "B2B TETRIS");
+        // A single breaks it, and comes with a combo.
+        let clear = scoring.lock(1, TSpin::None, false, 1).expect("a single is worth points");
+        assert!(!clear.back_to_back);
+        assert_eq!(clear.combo, 1);
+        assert_eq!(clear.points, 100 + 50);
+        let clear = scoring.lock(4, TSpin::None, false, 1).expect("a tetris is worth points");
+        assert!(!clear.back_to_back);
+        assert_eq!(scoring.stats().back_to_backs, 1);
+        assert_eq!(scoring.stats().count(4, TSpin::None), 3);
+        assert_eq!(scoring.stats().max_combo, 2);
+    }
+
+    #[test]
+    fn perfect_clear_points() {
+        let clear = Scoring::new().lock(1, TSpin::None, true, 1).expect("a perfect clear is worth points");
+        assert_eq!(clear.points, 100 + 800);
+        let mut scoring = Scoring::new();
+        scoring.lock(4, TSpin::None, false, 1);
+        let clear = scoring.lock(4, TSpin::None, true, 1).expect("a perfect clear is worth points");
+        assert_eq!(clear.points, 1200 + 50 + 3200);
+    }
+}
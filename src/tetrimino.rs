@@ -1,41 +1,192 @@
-extern crate rand;
+use crate::board::Board;
+use crate::rotation::RotationSystem;
+
+// The seven tetriminos, named after the letter they look like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    I,
+    J,
+    L,
+    O,
+    S,
+    Z,
+    T,
+}
+
+impl PieceKind {
+    pub const ALL: [PieceKind; 7] = [
+        PieceKind::I,
+        PieceKind::J,
+        PieceKind::L,
+        PieceKind::O,
+        PieceKind::S,
+        PieceKind::Z,
+        PieceKind::T,
+    ];
+
+    // Finds a tetrimino from the letter it's named after, whatever its case.
+    pub fn from_letter(letter: char) -> Option<PieceKind> {
+        match letter.to_ascii_uppercase() {
+            'I' => Some(PieceKind::I),
+            'J' => Some(PieceKind::J),
+            'L' => Some(PieceKind::L),
+            'O' => Some(PieceKind::O),
+            'S' => Some(PieceKind::S),
+            'Z' => Some(PieceKind::Z),
+            'T' => Some(PieceKind::T),
+            _ => None,
+        }
+    }
+}
+
+// Which way a rotation goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Clockwise,
+    CounterClockwise,
+}
 
 // We have two ways of handling the tetrimino rotation:
 // using matrix rotation or storing the different states.
 // To have a code that easy to read and update, I picked the second option,
 // but it'd nice to try using matrix later, it could help to learn a lot of things.
-pub(crate) struct Tetrimino {
+pub struct Tetrimino {
+    kind: PieceKind,
     states: Vec<Vec<Vec<u8>>>,
+    // The same states as occupancy masks, one `u16` per line, like the lines of the board.
+    // They're computed once here so the collision checks don't have to look at every cell.
+    masks: Vec<Vec<u16>>,
     x: isize,
-    y: usize,
+    // A wall kick can push the piece up, above the top of the game map,
+    // so unlike what we could think, `y` can be negative.
+    y: isize,
     current_state: u8,
+    // How many cells of the game map every block takes on each side: 2 in big mode.
+    scale: usize,
 }
 
 impl Tetrimino {
-    fn rotate(&mut self, game_map: &[Vec<u8>]) {
+    // Creates a tetrimino in its spawn state, in the top-left corner of the game map:
+    // `move_to_spawn` puts it where it belongs.
+    // The rotation system decides what its states look like.
+    pub fn new(kind: PieceKind, rotation_system: &dyn RotationSystem) -> Tetrimino {
+        Tetrimino::with_scale(kind, rotation_system, 1)
+    }
+
+    // The same, but every block of the states is made of `scale` by `scale` cells,
+    // like in the big mode where a block is 2x2. The states are scaled once and for all,
+    // so everything else (the masks, the collisions, the drawing) works the same.
+    pub fn with_scale(kind: PieceKind, rotation_system: &dyn RotationSystem, scale: usize) -> Tetrimino {
+        let scale = scale.max(1);
+        let states = rotation_system
+            .states(kind)
+            .into_iter()
+            .map(|state| {
+                state
+                    .iter()
+                    .flat_map(|line| {
+                        let line = line.iter().flat_map(|&cell| vec![cell; scale]).collect::<Vec<_>>();
+                        vec![line; scale]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let masks = states
+            .iter()
+            .map(|state| {
+                state
+                    .iter()
+                    .map(|line| {
+                        line.iter()
+                            .enumerate()
+                            .filter(|&(_, &cell)| cell != 0)
+                            .fold(0, |mask, (x, _)| mask | 1 << x)
+                    })
+                    .collect()
+            })
+            .collect();
+        Tetrimino {
+            kind,
+            states,
+            masks,
+            x: 0,
+            y: 0,
+            current_state: rotation_system.spawn_state(kind) as u8,
+            scale,
+        }
+    }
+
+    // The renderer needs to know where the piece is and what it looks like,
+    // but it mustn't be able to move it around without going through the checks below.
+    pub fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    pub fn x(&self) -> isize {
+        self.x
+    }
+
+    pub fn y(&self) -> isize {
+        self.y
+    }
+
+    pub fn current_state(&self) -> usize {
+        self.current_state as usize
+    }
+
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    // Returns the 4x4 grid of the state the tetrimino is currently in (8x8 in big mode).
+    pub fn shape(&self) -> &[Vec<u8>] {
+        self.state(self.current_state as usize)
+    }
+
+    // Returns the 4x4 grid of any of the tetrimino's states.
+    pub fn state(&self, state: usize) -> &[Vec<u8>] {
+        &self.states[state]
+    }
+
+    // The occupancy mask of any of the tetrimino's states: bit `x` of line `y`
+    // is set when the state has a block at (x, y).
+    pub fn mask(&self, state: usize) -> &[u16] {
+        &self.masks[state]
+    }
+
+    // Rotates the tetrimino in the given direction, if it can be done.
+    // Returns which kick made it fit (0 being no kick at all), or `None` if it didn't rotate.
+    pub(crate) fn rotate(&mut self,
+                         game_map: &Board,
+                         rotation: Rotation,
+                         rotation_system: &dyn RotationSystem) -> Option<usize> {
         // A bit longer, indeed. Since we can't be sure that
         // the piece will be put where we want it to go, we need to make temporary variables
         // and then check the possibilities. We use the temporary variables before going further.
-        let mut tmp_state = self.current_state + 1;
-        if tmp_state as usize >= self.states.len() {
-            tmp_state = 0;
-        }
-        // This line its own doesn't make much sense but it'll be very useful next:
-        // in case the piece cannot be placed where we want, we try to move it on the `x` axis
-        // to see if it'd work in some other place. It allows you to have a Tetris
-        // that is much more flexible and comfortable to play
-        let x_pos = [0, -1, 1, -2, 2, -3];
-        // With the explanations given previously, this loop should be really easy to understand.
-        // For each x shift, we check whether the piece can be placed there.
+        let nb_states = self.states.len();
+        let current_state = self.current_state as usize;
+        let tmp_state = match rotation {
+            Rotation::Clockwise => (current_state + 1) % nb_states,
+            Rotation::CounterClockwise => (current_state + nb_states - 1) % nb_states,
+        };
+        // In case the piece cannot be placed where we want, the rotation system tells us
+        // where else to try, so we can see if it'd work in some other place.
+        // For each kick, we check whether the piece can be placed there.
         // If it works, we change the values of our tetrimino, otherwise we just continue.
-        // If no `x` shift worked, we just leave the function without doing anything.
-        for x in x_pos.iter() {
-            if self.test_position(game_map, tmp_state as usize, self.x + x, self.y) == true {
-                self.current_state = tmp_state;
-                self.x += *x;
-                break;
+        // If no kick worked, we just leave the function without doing anything.
+        // The kicks are in blocks, so they're as big as the blocks are.
+        let kicks = rotation_system.kicks(self, game_map, tmp_state);
+        let scale = self.scale as isize;
+        for (kick, (kick_x, kick_y)) in kicks.into_iter().enumerate() {
+            let (kick_x, kick_y) = (kick_x * scale, kick_y * scale);
+            if self.test_position(game_map, tmp_state, self.x + kick_x, self.y + kick_y) {
+                self.current_state = tmp_state as u8;
+                self.x += kick_x;
+                self.y += kick_y;
+                return Some(kick);
             }
         }
+        None
     }
 
     // Now that we can rotate and test the position of a tetrimino,
@@ -51,9 +202,9 @@ impl Tetrimino {
     //
     // If we can put the tetrimino in a place, we update the position of the tetrimino and return true,
     // otherwise, we do nothing other than return false.
-    fn change_position(&mut self, game_map: &[Vec<u8>], new_x: isize, new_y: usize) -> bool {
-        if self.test_position(game_map, self.current_state as usize, new_x, new_y) == true {
-            self.x = new_x as isize;
+    pub(crate) fn change_position(&mut self, game_map: &Board, new_x: isize, new_y: isize) -> bool {
+        if self.test_position(game_map, self.current_state as usize, new_x, new_y) {
+            self.x = new_x;
             self.y = new_y;
             true
         } else {
@@ -61,252 +212,50 @@ impl Tetrimino {
         }
     }
 
-    // It seems important to explain why the game map became a &[Vec<u8>].
-    // When we send a non-mutable reference over a vector (Vec<T>),
-    // it is then dereferenced into a & &[T] slice,
-    // which is a constant `view` over the vector's content.
+    // Puts the tetrimino in the middle of a game map of the given width (a bit to the left
+    // when it can't be exactly in the middle), with its lowest blocks on the last hidden row,
+    // right above the part of the game map the player sees.
+    // A big piece also stays on the grid of 2x2 cells, so it's always in line with the others.
     //
-    // We loop over every block of our `tetrimino`
-    // and check whether the block is free in the game map
-    // (by checking whether it is equal to 0) and if it isn't going out of the game map.
-    fn test_position(&self, game_map: &[Vec<u8>], tmp_state: usize, x: isize, y: usize) -> bool {
-        for decal_y in 0..4 {
-            for decal_x in 0..4 {
-                let x = x + decal_x;
-                if self.states[tmp_state][decal_y][decal_x as usize] != 0
-                    && (y + decal_y >= game_map.len()
-                    || x < 0
-                    || x as usize >= game_map[y + decal_y].len()
-                    || game_map[y + decal_y][x as usize] != 0
-                ) {
-                    return false;
-                }
-            }
-        }
-        return true;
-    }
-}
-
-pub trait TetriminoGenerator {
-    fn new() -> Tetrimino;
-}
-
-pub(crate) struct TetriminoI;
-
-pub(crate) struct TetriminoJ;
-
-pub(crate) struct TetriminoL;
-
-pub(crate) struct TetriminoO;
-
-pub(crate) struct TetriminoS;
-
-pub(crate) struct TetriminoZ;
-
-pub(crate) struct TetriminoT;
-
-impl TetriminoGenerator for TetriminoI {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            // In here, a number represents a color and zero means no color
-            // (because there's no block).
-            states: vec![vec![vec![1, 1, 1, 1],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ],
-                         vec![vec![1, 0, 0, 0],
-                              vec![1, 0, 0, 0],
-                              vec![1, 0, 0, 0],
-                              vec![1, 0, 0, 0],
-                         ]
-            ],
-            x: 4,
-            y: 0,
-            current_state: 0,
-        }
-    }
-}
-
-impl TetriminoGenerator for TetriminoJ {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            states: vec![vec![vec![2, 2, 2, 0],
-                              vec![0, 0, 2, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ],
-                         vec![vec![0, 2, 0, 0],
-                              vec![0, 2, 0, 0],
-                              vec![2, 2, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-                         vec![vec![2, 0, 0, 0],
-                              vec![2, 2, 2, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-                         vec![vec![2, 2, 0, 0],
-                              vec![2, 0, 0, 0],
-                              vec![2, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-            ],
-            x: 4,
-            y: 0,
-            current_state: 0,
-        }
+    // The states don't all have their blocks in the same place of their grid,
+    // so we look for the blocks themselves rather than the grid.
+    pub(crate) fn move_to_spawn(&mut self, width: usize, hidden_rows: usize) {
+        let blocks = self
+            .shape()
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| line.iter().enumerate().filter(|&(_, &cell)| cell != 0).map(move |(x, _)| (x, y)))
+            .collect::<Vec<_>>();
+        let left = blocks.iter().map(|&(x, _)| x).min().unwrap_or(0) as isize;
+        let right = blocks.iter().map(|&(x, _)| x).max().unwrap_or(0) as isize;
+        let bottom = blocks.iter().map(|&(_, y)| y).max().unwrap_or(0) as isize;
+        self.x = (width as isize - (right - left + 1)) / 2 - left;
+        self.x -= self.x.rem_euclid(self.scale as isize);
+        self.y = hidden_rows as isize - 1 - bottom;
     }
-}
 
-impl TetriminoGenerator for TetriminoL {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            // The answer for: Why the blokcs have `2` as value,
-            // it's simple so that we can differentiate them when displaying
-            // (having all tetrimino with the same color wouldn't be very pretty).
-            // It has no other meaning.
-            states: vec![vec![vec![3, 3, 3, 0],
-                              vec![3, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ],
-                         vec![vec![3, 3, 0, 0],
-                              vec![0, 3, 0, 0],
-                              vec![0, 3, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-                         vec![vec![0, 0, 3, 0],
-                              vec![3, 3, 3, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-                         vec![vec![3, 0, 0, 0],
-                              vec![3, 0, 0, 0],
-                              vec![3, 3, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-            ],
-            x: 4,
-            y: 0,
-            current_state: 0,
-        }
+    // Checks whether the tetrimino, as it is right now, fits in the game map.
+    // A freshly spawned piece failing this check means the stack reached the top.
+    pub(crate) fn is_valid(&self, game_map: &Board) -> bool {
+        self.test_position(game_map, self.current_state as usize, self.x, self.y)
     }
-}
 
-impl TetriminoGenerator for TetriminoO {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            states: vec![vec![vec![4, 4, 0, 0],
-                              vec![4, 4, 0, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ]],
-            x: 4,
-            y: 0,
-            current_state: 0,
+    // Returns the `y` the tetrimino would end up at if it kept falling straight down.
+    // We just try every row below until the piece doesn't fit anymore
+    // (every other row in big mode, since a big piece goes down by two).
+    pub(crate) fn landing_y(&self, game_map: &Board) -> isize {
+        let mut y = self.y;
+        let step = self.scale as isize;
+        while self.test_position(game_map, self.current_state as usize, self.x, y + step) {
+            y += step;
         }
+        y
     }
-}
-
-impl TetriminoGenerator for TetriminoS {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            states: vec![vec![vec![0, 5, 5, 0],
-                              vec![5, 5, 0, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ],
-                         vec![vec![0, 5, 0, 0],
-                              vec![0, 5, 5, 0],
-                              vec![0, 0, 5, 0],
-                              vec![0, 0, 0, 0],
-                         ]
-            ],
-            x: 4,
-            y: 0,
-            current_state: 0,
-        }
-    }
-}
-
-impl TetriminoGenerator for TetriminoZ {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            states: vec![vec![vec![6, 6, 0, 0],
-                              vec![0, 6, 6, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ],
-                         vec![vec![0, 0, 6, 0],
-                              vec![0, 6, 6, 0],
-                              vec![0, 6, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-            ],
-            x: 4,
-            y: 0,
-            current_state: 0,
-        }
-    }
-}
-
-impl TetriminoGenerator for TetriminoT {
-    fn new() -> Tetrimino {
-        Tetrimino {
-            states: vec![vec![vec![7, 7, 7, 0],
-                              vec![0, 7, 0, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-            ],
-                         vec![vec![0, 7, 0, 0],
-                              vec![7, 7, 0, 0],
-                              vec![0, 7, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-                         vec![vec![0, 7, 0, 0],
-                              vec![7, 7, 7, 0],
-                              vec![0, 0, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ],
-                         vec![vec![0, 7, 0, 0],
-                              vec![0, 7, 7, 0],
-                              vec![0, 7, 0, 0],
-                              vec![0, 0, 0, 0],
-                         ]
-            ],
-            x: 4,
-            y: 0,
-            current_state: 0,
-        }
-    }
-}
-
-fn create_new_tetrimino() -> Tetrimino {
-    // If we just call rand::random(), this is a bit too random.
-    // It's d be problematic if we had the same tetrimino generated more than twice in a row.
-    // (which is already a lot!), so we need to improve it by adding a static variable.
-    // A bit explanation might be helpful here.
-    // First, what is a `static` variable?
-    // That is a variable that will keep its value
-    // and won't be destroyed when the scope it has been created inside has been left.
-    static mut PREV: u8 = 7;
-    let mut rand_nb = rand::random::<u8>() % 7;
-
-    if unsafe { PREV } == rand_nb {
-        rand_nb = rand::random::<u8>() % 7;
-    }
-
-    unsafe { PREV = rand_nb; }
 
-    match rand_nb {
-        0 => TetriminoI::new(),
-        1 => TetriminoJ::new(),
-        2 => TetriminoL::new(),
-        3 => TetriminoO::new(),
-        4 => TetriminoS::new(),
-        5 => TetriminoZ::new(),
-        6 => TetriminoT::new(),
-        _ => unreachable!(),
+    // Checks whether the given state of the tetrimino fits at (x, y): none of its blocks
+    // may be out of the game map or on a block which is already there.
+    // The board does the work with the mask of the state, a line at a time.
+    pub(crate) fn test_position(&self, game_map: &Board, tmp_state: usize, x: isize, y: isize) -> bool {
+        game_map.fits(&self.masks[tmp_state], x, y)
     }
 }
@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+// Measures how long a game lasts. It uses `Instant`, which only ever goes forward:
+// unlike the time of the system, it can't jump because the clock of the computer
+// got changed in the middle of a game.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stopwatch {
+    started_at: Option<Instant>,
+    // Once stopped, the time doesn't move anymore.
+    stopped_after: Option<Duration>,
+}
+
+impl Stopwatch {
+    // Starts counting, unless it's already been done.
+    pub fn start(&mut self) {
+        if self.started_at.is_none() {
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if self.stopped_after.is_none() {
+            self.stopped_after = Some(self.elapsed());
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some() && self.stopped_after.is_none()
+    }
+
+    // The time since it started (and until it stopped, if it did).
+    // Nothing has elapsed yet if it didn't start.
+    pub fn elapsed(&self) -> Duration {
+        match (self.started_at, self.stopped_after) {
+            (_, Some(elapsed)) => elapsed,
+            (Some(started_at), None) => started_at.elapsed(),
+            (None, None) => Duration::from_secs(0),
+        }
+    }
+}
+
+// Writes a time with its milliseconds, like `1:23.456`.
+pub fn format_time(time: Duration) -> String {
+    let millis = time.as_millis();
+    format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
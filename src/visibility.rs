@@ -0,0 +1,26 @@
+use crate::input::Timing;
+
+// How much of the stack the player gets to see. The engine doesn't care at all:
+// the blocks are in the game map either way, it's up to the frontend to hide them.
+// Everything is shown again once the game is over, like at the end of TGM's M-roll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StackVisibility {
+    #[default]
+    Visible,
+    // The blocks disappear as soon as the piece locks.
+    Invisible,
+    // The blocks stay visible for the given time after the piece locks, then disappear.
+    Fading(Timing),
+}
+
+impl StackVisibility {
+    // How many frames a block stays visible once it's part of the stack,
+    // or `None` if it never disappears.
+    pub fn visible_frames(self) -> Option<u32> {
+        match self {
+            StackVisibility::Visible => None,
+            StackVisibility::Invisible => Some(0),
+            StackVisibility::Fading(timing) => Some(timing.to_frames()),
+        }
+    }
+}